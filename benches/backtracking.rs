@@ -0,0 +1,214 @@
+//! Compares the ways solvers have checked digits against the standard rules:
+//! - `hash-set`: the original check, which collected the digits in the
+//!   square's row, column and box into a `HashSet` apiece.
+//! - `scan`: [`Standard::allows`](sudoku::puzzle::Standard), which compares
+//!   the digit with each of the square's peers in turn.
+//! - `occupancy`: the constant time checks which [`BaseSolution`] makes now,
+//!   by keeping track of the digits in each row, column and region.
+//!
+//! The same goes for [`Backtracking`], which makes one of these checks for
+//! every edit.
+
+use std::hint::black_box;
+use std::str::FromStr;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use sudoku::{Algorithm, Backtracking, BaseSolution, Puzzle};
+
+const EASY: &str = "050703060007000800000816000000030000005000100730040086906000204840572093000409000";
+/// Arto Inkala's "world's hardest Sudoku", which takes backtracking about half a
+/// million edits.
+const HARD: &str = "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..";
+
+/// The check which `BaseSolution` made before puzzles had constraints, copied
+/// as it was but reading from a [`Puzzle`] and with its nested `if`s joined,
+/// which only works for 9x9 puzzles.
+mod hash_set {
+    use std::collections::HashSet;
+    use std::ops::Range;
+
+    use sudoku::Puzzle;
+
+    const PUZZLE_DIGITS: usize = 81;
+
+    pub fn is_valid_digit(puzzle: &Puzzle, idx: usize) -> bool {
+        if idx >= PUZZLE_DIGITS {
+            return false;
+        }
+        let (row, col) = (idx / 9, idx % 9);
+        let (rowr, colr) = box_ranges(row, col);
+
+        // Per the rules of Sudoku, every square must have a unique value among its row,
+        // column, and box.
+        is_valid_row(puzzle, row) && is_valid_col(puzzle, col) && is_valid_box(puzzle, rowr, colr)
+    }
+
+    fn is_valid_row(puzzle: &Puzzle, row: usize) -> bool {
+        let mut seen = HashSet::new();
+        for col in 0..9 {
+            let idx = row * 9 + col;
+            if let Some(digit) = puzzle.get(idx)
+                && !seen.insert(digit)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn is_valid_col(puzzle: &Puzzle, col: usize) -> bool {
+        let mut seen = HashSet::new();
+        for row in 0..9 {
+            let idx = row * 9 + col;
+            if let Some(digit) = puzzle.get(idx)
+                && !seen.insert(digit)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn is_valid_box(puzzle: &Puzzle, rowr: Range<usize>, colr: Range<usize>) -> bool {
+        let mut seen = HashSet::new();
+        for row in rowr {
+            for col in colr.clone() {
+                let idx = row * 9 + col;
+                if let Some(digit) = puzzle.get(idx)
+                    && !seen.insert(digit)
+                {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// For the box which the given `row` and `col` lie within, return a tuple of
+    /// the (row range, column range) which describes that box.
+    fn box_ranges(row: usize, col: usize) -> (Range<usize>, Range<usize>) {
+        match row {
+            0..3 => match col {
+                0..3 => (0..3, 0..3),
+                3..6 => (0..3, 3..6),
+                6..9 => (0..3, 6..9),
+                _ => unreachable!(),
+            },
+            3..6 => match col {
+                0..3 => (3..6, 0..3),
+                3..6 => (3..6, 3..6),
+                6..9 => (3..6, 6..9),
+                _ => unreachable!(),
+            },
+            6..9 => match col {
+                0..3 => (6..9, 0..3),
+                3..6 => (6..9, 3..6),
+                6..9 => (6..9, 6..9),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Returns true if the digit in the square at `index` follows every one of
+/// the puzzle's constraints, checked by scanning its peers.
+fn scan(puzzle: &Puzzle, index: usize) -> bool {
+    puzzle.get(index).is_none_or(|digit| puzzle.constraints().all(|constraint| constraint.allows(puzzle, index, digit)))
+}
+
+/// The same search as [`Backtracking`], with each digit checked by
+/// `is_valid`. Returns false if the puzzle has no solution.
+fn backtrack(puzzle: &mut Puzzle, is_valid: fn(&Puzzle, usize) -> bool) -> bool {
+    // This cast to u8 is safe since puzzles are never more than 25 squares across.
+    let highest_digit = puzzle.dimensions.size() as u8;
+    let initially_empty: Vec<_> = (0..puzzle.data.len()).filter(|&index| puzzle.get(index).is_none()).collect();
+    let mut pointer = 0;
+    while pointer < initially_empty.len() {
+        let index = initially_empty[pointer];
+        let base = puzzle.get(index).unwrap_or(0);
+        let found_valid = ((base + 1)..=highest_digit).any(|digit| {
+            puzzle.set(index, Some(digit));
+            is_valid(puzzle, index)
+        });
+        if found_valid {
+            pointer += 1;
+        } else {
+            puzzle.set(index, None);
+            let Some(previous) = pointer.checked_sub(1) else {
+                return false;
+            };
+            pointer = previous;
+        }
+    }
+    true
+}
+
+fn validity(c: &mut Criterion) {
+    let puzzle = Puzzle::from_str(EASY).unwrap();
+    let empty: Vec<_> = (0..puzzle.data.len()).filter(|&index| puzzle.get(index).is_none()).collect();
+
+    // Try every digit in every empty square, as a solver would.
+    let mut group = c.benchmark_group("validity");
+    for (name, is_valid) in [("hash-set", hash_set::is_valid_digit as fn(&Puzzle, usize) -> bool), ("scan", scan)] {
+        let mut puzzle = puzzle.clone();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut valid = 0;
+                for &index in &empty {
+                    for digit in 1..=9 {
+                        puzzle.set(index, Some(digit));
+                        valid += usize::from(is_valid(black_box(&puzzle), index));
+                    }
+                    puzzle.set(index, None);
+                }
+                valid
+            })
+        });
+    }
+    let mut solution = BaseSolution::new(puzzle);
+    group.bench_function("occupancy", |b| {
+        b.iter(|| {
+            let mut valid = 0;
+            for &index in &empty {
+                for digit in 1..=9 {
+                    solution.set(index, Some(digit));
+                    valid += usize::from(black_box(&mut solution).is_valid_digit(index));
+                }
+                solution.set(index, None);
+            }
+            valid
+        })
+    });
+    group.finish();
+}
+
+fn backtracking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("backtracking");
+    // The hard puzzle takes the original check a good fraction of a second.
+    group.sample_size(20);
+    for (name, puzzle) in [("easy", EASY), ("hard", HARD)] {
+        let puzzle = Puzzle::from_str(puzzle).unwrap();
+        for (check, is_valid) in [("hash-set", hash_set::is_valid_digit as fn(&Puzzle, usize) -> bool), ("scan", scan)]
+        {
+            group.bench_function(format!("{check}/{name}"), |b| {
+                b.iter(|| {
+                    let mut puzzle = black_box(puzzle.clone());
+                    assert!(backtrack(&mut puzzle, is_valid));
+                    puzzle
+                })
+            });
+        }
+        group.bench_function(format!("occupancy/{name}"), |b| {
+            b.iter(|| {
+                let mut solution = BaseSolution::new(black_box(puzzle.clone()));
+                Backtracking.solve(&mut solution, None).unwrap();
+                solution
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, validity, backtracking);
+criterion_main!(benches);
@@ -0,0 +1,95 @@
+//! Benchmarks parsing, validating and solving the puzzles in
+//! `benches/puzzles`, which has a file for each category:
+//! - `easy`: puzzles of the sort printed in newspapers.
+//! - `hard`: puzzles published as especially hard for people, such as Arto
+//!   Inkala's "world's hardest Sudoku".
+//! - `seventeen`: puzzles with 17 givens, the fewest that a puzzle with a
+//!   single solution can have.
+//! - `pathological`: puzzles designed to defeat naive backtracking, which
+//!   guesses wrong in the first few empty squares and takes hundreds of
+//!   millions of edits to find out.
+
+use std::hint::black_box;
+use std::str::FromStr;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use sudoku::format::Format;
+use sudoku::{Algorithm, AlgorithmKind, BaseSolution, ConstraintPropagation, Limits, Puzzle};
+
+const CATEGORIES: [(&str, &str); 4] = [
+    ("easy", include_str!("puzzles/easy.sdm")),
+    ("hard", include_str!("puzzles/hard.sdm")),
+    ("seventeen", include_str!("puzzles/seventeen.sdm")),
+    ("pathological", include_str!("puzzles/pathological.sdm")),
+];
+
+/// Backtracking takes hundreds of millions of edits to solve the 17-clue and
+/// pathological puzzles, so the solvers give up after this many, which is
+/// still plenty to compare between runs.
+const MAX_STEPS: u64 = 1_000_000;
+
+/// The puzzles in each category.
+fn corpus() -> Vec<(&'static str, Vec<Puzzle>)> {
+    let puzzles = |text| Format::Sdm.parse(text).unwrap().into_iter().map(|entry| entry.puzzle).collect();
+    CATEGORIES.iter().map(|&(name, text)| (name, puzzles(text))).collect()
+}
+
+fn parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for ((name, text), (_, puzzles)) in CATEGORIES.iter().zip(corpus()) {
+        group.bench_function(format!("sdm/{name}"), |b| b.iter(|| Format::Sdm.parse(black_box(text)).unwrap()));
+
+        // The grids as the solver prints them, with a line per row and split into boxes.
+        let grids: Vec<_> = puzzles.iter().map(Puzzle::to_string).collect();
+        group.bench_function(format!("grid/{name}"), |b| {
+            b.iter(|| grids.iter().map(|grid| Puzzle::from_str(black_box(grid)).unwrap()).collect::<Vec<_>>())
+        });
+    }
+    group.finish();
+}
+
+fn validation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("validate");
+    for (name, puzzles) in corpus() {
+        let solutions: Vec<_> = puzzles
+            .iter()
+            .map(|puzzle| {
+                let mut solution = BaseSolution::new(puzzle.clone());
+                ConstraintPropagation.solve(&mut solution, None).unwrap();
+                solution.into_puzzle()
+            })
+            .collect();
+
+        group.bench_function(format!("givens/{name}"), |b| {
+            b.iter(|| puzzles.iter().filter(|puzzle| black_box(puzzle).validate().is_ok()).count())
+        });
+        group.bench_function(format!("solutions/{name}"), |b| {
+            b.iter(|| solutions.iter().filter(|solution| black_box(solution).validate().is_ok()).count())
+        });
+    }
+    group.finish();
+}
+
+fn solving(c: &mut Criterion) {
+    let limits = Limits { max_steps: Some(MAX_STEPS), ..Limits::default() };
+    for algorithm in [AlgorithmKind::Backtracking, AlgorithmKind::Propagation] {
+        let mut group = c.benchmark_group(format!("solve/{algorithm}"));
+        // Even with the limit, a pass over some categories takes a good fraction of a
+        // second.
+        group.sample_size(10);
+        for (name, puzzles) in corpus() {
+            group.bench_function(name, |b| {
+                b.iter(|| {
+                    for puzzle in &puzzles {
+                        let mut solution = BaseSolution::new(black_box(puzzle.clone())).with_limits(limits);
+                        algorithm.solve(&mut solution, None).unwrap();
+                    }
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, parsing, validation, solving);
+criterion_main!(benches);
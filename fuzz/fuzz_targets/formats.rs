@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sudoku::format::Format;
+
+fuzz_target!(|text: &str| {
+    // Anything may be rejected, but nothing may panic, even when written back out.
+    for format in Format::ALL.into_iter().chain([Format::detect(None, text)]) {
+        if let Ok(entries) = format.parse(text) {
+            let _ = format.write(&entries);
+        }
+    }
+});
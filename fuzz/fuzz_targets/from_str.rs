@@ -0,0 +1,13 @@
+#![no_main]
+
+use std::str::FromStr;
+
+use libfuzzer_sys::fuzz_target;
+use sudoku::Puzzle;
+
+fuzz_target!(|text: &str| {
+    // Anything may be rejected, but nothing may panic, even once parsed.
+    if let Ok(puzzle) = Puzzle::from_str(text) {
+        let _ = (puzzle.to_string(), puzzle.serialize_rules(), puzzle.validate());
+    }
+});
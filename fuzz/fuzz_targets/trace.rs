@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sudoku::trace;
+
+fuzz_target!(|text: &str| {
+    // Anything may be rejected, but nothing may panic, even when replayed.
+    if let Ok((mut puzzle, edits)) = trace::parse(text) {
+        for edit in &edits {
+            edit.apply(&mut puzzle);
+        }
+    }
+});
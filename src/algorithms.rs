@@ -1,16 +1,182 @@
-use std::sync::mpsc::Receiver;
+use std::fmt;
+use std::time::Duration;
 
 use anyhow::Result;
+use serde::Serialize;
 
+use crate::cancel::CancellationToken;
 use crate::solution::Solution;
 
 pub mod backtracking;
+pub mod propagation;
 pub use backtracking::Backtracking;
+pub use propagation::ConstraintPropagation;
 
 pub trait Algorithm {
     /// Solve the puzzle; this is done in-place.
     ///
-    /// Implementors must listen on `kill_channel` (if provided) and cleanly
-    /// cancel their computation if a message is received.
-    fn solve<T: Solution>(&self, solution: &mut T, kill_channel: Option<Receiver<()>>) -> Result<()>;
+    /// Implementors must fail if the puzzle already breaks its rules, see
+    /// [`BaseSolution::check_puzzle`](crate::BaseSolution::check_puzzle).
+    ///
+    /// Implementors must check `cancel` (if provided) as they go, and cleanly
+    /// stop their computation once it is cancelled. They must also
+    /// check [`BaseSolution::limit_reached`](crate::BaseSolution::limit_reached)
+    /// as they go, and give up once it returns a limit, and call
+    /// [`BaseSolution::report_progress`](crate::BaseSolution::report_progress)
+    /// just as often.
+    fn solve<T: Solution>(&self, solution: &mut T, cancel: Option<&CancellationToken>) -> Result<Outcome>;
+}
+
+/// How a call to [`Algorithm::solve`] ended, other than by failing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Solved,
+    /// The solve's [`CancellationToken`] was cancelled.
+    Cancelled,
+    /// The solver stopped after reaching one of the solution's [`Limits`],
+    /// leaving the puzzle partly filled in.
+    GaveUp(Limit),
+}
+
+/// Bounds on how much work a solver may do before giving up, so that a
+/// pathological puzzle can't run forever.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Limits {
+    /// How long the solver may run for.
+    pub timeout: Option<Duration>,
+    /// How many edits the solver may make to the puzzle. Solvers check this
+    /// as they move between squares, or before each pass over the puzzle, so
+    /// they may make a few more.
+    pub max_steps: Option<u64>,
+}
+
+/// Which of the [`Limits`] a solver reached.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Limit {
+    Timeout,
+    MaxSteps,
+}
+
+/// Selects one of the [`Algorithm`]s at runtime.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum AlgorithmKind {
+    /// Try each digit in each square in order, backing up on conflicts
+    #[default]
+    Backtracking,
+    /// Fill in forced squares before guessing, guessing where there are fewest
+    /// options
+    Propagation,
+}
+
+impl Algorithm for AlgorithmKind {
+    fn solve<T: Solution>(&self, solution: &mut T, cancel: Option<&CancellationToken>) -> Result<Outcome> {
+        match self {
+            Self::Backtracking => Backtracking.solve(solution, cancel),
+            Self::Propagation => ConstraintPropagation.solve(solution, cancel),
+        }
+    }
+}
+
+impl fmt::Display for AlgorithmKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Backtracking => "backtracking",
+            Self::Propagation => "propagation",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::Timeout => "ran out of time",
+            Self::MaxSteps => "reached the maximum number of steps",
+        };
+        f.write_str(description)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use proptest::prelude::*;
+    use proptest::{bool, collection};
+
+    use super::*;
+    use crate::puzzle::Puzzle;
+    use crate::solution::base::BaseSolution;
+
+    const SOLVED: &str = "158723469367954821294816375619238547485697132732145986976381254841572693523469718";
+
+    /// A puzzle with a solution, made by relabelling the digits of a solved
+    /// grid and emptying some of its squares.
+    fn solvable_puzzle() -> impl Strategy<Value = Puzzle> {
+        let digits = Just((1..=9).collect::<Vec<u8>>()).prop_shuffle();
+        (digits, collection::vec(bool::weighted(0.4), 81)).prop_map(|(digits, givens)| {
+            let mut puzzle = Puzzle::from_str(SOLVED).unwrap();
+            for (index, given) in givens.into_iter().enumerate() {
+                let digit = puzzle.get(index).map(|digit| digits[digit as usize - 1]);
+                puzzle.data[index] = digit.filter(|_| given);
+            }
+            puzzle
+        })
+    }
+
+    /// A 4x4 puzzle whose givens don't repeat a digit, which may or may not
+    /// have a solution.
+    fn consistent_puzzle() -> impl Strategy<Value = Puzzle> {
+        let squares = collection::vec(prop_oneof![3 => Just(None), 1 => (1..=4u8).prop_map(Some)], 16);
+        squares
+            .prop_map(|data| Puzzle { data, ..Puzzle::from_str(&".".repeat(16)).unwrap() })
+            .prop_filter("givens repeat a digit", |puzzle| puzzle.validate().is_ok())
+    }
+
+    /// Check that a solve which claims to have succeeded filled in the puzzle
+    /// without breaking any rules or changing the givens.
+    fn check_solution(puzzle: &Puzzle, algorithm: AlgorithmKind) -> std::result::Result<(), TestCaseError> {
+        // Backtracking can take a very long time over some puzzles, which is fine as long
+        // as it gives up.
+        let limits = Limits { max_steps: Some(1_000_000), ..Limits::default() };
+        let mut solution = BaseSolution::new(puzzle.clone()).with_limits(limits);
+        if let Ok(Outcome::Solved) = algorithm.solve(&mut solution, None) {
+            prop_assert!(solution.puzzle().is_filled_out(), "{algorithm}");
+            prop_assert_eq!(solution.puzzle().validate(), Ok(()), "{}", algorithm);
+            for (index, given) in puzzle.data.iter().enumerate().filter(|(_, given)| given.is_some()) {
+                prop_assert_eq!(solution.puzzle().get(index), *given, "{}", algorithm);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_broken_givens() {
+        let puzzle = Puzzle::from_str("1.1.............").unwrap();
+        for algorithm in [AlgorithmKind::Backtracking, AlgorithmKind::Propagation] {
+            let error = algorithm.solve(&mut BaseSolution::new(puzzle.clone()), None).unwrap_err();
+            assert_eq!(error.to_string(), "the puzzle already breaks its rules at r1c1, r1c3", "{algorithm}");
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_solutions_are_valid(puzzle in solvable_puzzle()) {
+            for algorithm in [AlgorithmKind::Backtracking, AlgorithmKind::Propagation] {
+                check_solution(&puzzle, algorithm)?;
+            }
+            let mut solution = BaseSolution::new(puzzle);
+            prop_assert_eq!(ConstraintPropagation.solve(&mut solution, None).unwrap(), Outcome::Solved);
+        }
+
+        #[test]
+        fn test_unsolvable_puzzles_fail(puzzle in consistent_puzzle()) {
+            // The solvers must fail cleanly when there is no solution, rather than panic.
+            for algorithm in [AlgorithmKind::Backtracking, AlgorithmKind::Propagation] {
+                check_solution(&puzzle, algorithm)?;
+            }
+        }
+    }
 }
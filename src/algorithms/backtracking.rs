@@ -1,26 +1,30 @@
-use std::sync::mpsc::Receiver;
+use anyhow::{Result, anyhow};
 
-use anyhow::Result;
-
-use crate::algorithms::Algorithm;
+use crate::algorithms::{Algorithm, Outcome};
+use crate::cancel::CancellationToken;
 use crate::solution::Solution;
 
 pub struct Backtracking;
 
 impl Algorithm for Backtracking {
-    fn solve<T: Solution>(&self, solution: &mut T, kill_channel: Option<Receiver<()>>) -> Result<()> {
+    fn solve<T: Solution>(&self, solution: &mut T, cancel: Option<&CancellationToken>) -> Result<Outcome> {
+        solution.base().check_puzzle()?;
         let mut pointer = 0;
 
         // We only need to iterate over the initially empty squares to find a solution.
+        // This cast to u8 is safe since puzzles are never more than 25 squares across.
+        let highest_digit = solution.base().puzzle().dimensions.size() as u8;
         let initially_empty: Vec<_> =
             solution.base().iter_puzzle().filter(|(_, digit)| digit.is_none()).map(|(idx, _)| idx).collect();
 
         while pointer < initially_empty.len() {
-            if let Some(channel) = kill_channel.as_ref()
-                && let Ok(()) = channel.try_recv()
-            {
-                return Ok(());
+            if cancel.is_some_and(CancellationToken::is_cancelled) {
+                return Ok(Outcome::Cancelled);
+            }
+            if let Some(limit) = solution.base().limit_reached() {
+                return Ok(Outcome::GaveUp(limit));
             }
+            solution.base().report_progress(pointer, cancel);
 
             let idx = initially_empty[pointer];
             let base = solution.base().get(idx).unwrap_or(0);
@@ -30,8 +34,8 @@ impl Algorithm for Backtracking {
             // base will be 0 here and thus we will start at 1. If the square had a value,
             // we will only consider values higher than it since we've already tried the
             // lower values.
-            for cand in (base + 1)..=9 {
-                solution.set(idx, Some(cand))?;
+            for cand in (base + 1)..=highest_digit {
+                solution.set_with_reason(idx, Some(cand), "try")?;
                 if solution.base().is_valid_digit(idx) {
                     found_valid = true;
                     break;
@@ -41,25 +45,28 @@ impl Algorithm for Backtracking {
             if found_valid {
                 pointer += 1;
             } else {
-                solution.set(idx, None)?;
+                solution.set_with_reason(idx, None, "backtrack")?;
                 if pointer == 0 {
                     // If we ever get here, that means we've exhausted all the candidates in the
                     // first cell, and thus we cannot find a solution.
-                    panic!("unsolvable");
+                    return Err(anyhow!("puzzle has no solution"));
                 }
                 pointer -= 1;
             }
         }
 
-        Ok(())
+        Ok(Outcome::Solved)
     }
 }
 
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
     use super::*;
+    use crate::algorithms::{Limit, Limits};
     use crate::puzzle::Puzzle;
     use crate::solution::base::BaseSolution;
 
@@ -75,6 +82,73 @@ mod test {
             Puzzle::from_str("158723469367954821294816375619238547485697132732145986976381254841572693523469718")
                 .unwrap();
 
-        assert_eq!(solution.puzzle, expected);
+        assert_eq!(*solution.puzzle(), expected);
+    }
+
+    #[test]
+    fn test_solve_killer() {
+        // The grid starts out empty, so only the cages stop any old solution from
+        // being valid.
+        let puzzle = Puzzle::from_str(
+            "................
+             cage 4 r1c1 r2c1
+             cage 5 r1c2 r1c3
+             cage 6 r1c4 r2c4
+             cage 5 r2c2 r2c3
+             cage 6 r3c1 r4c1
+             cage 5 r3c2 r3c3
+             cage 4 r3c4 r4c4
+             cage 5 r4c2 r4c3",
+        )
+        .unwrap();
+        let mut solution = BaseSolution::new(puzzle);
+        Backtracking.solve(&mut solution, None).unwrap();
+
+        assert!(solution.puzzle().is_filled_out());
+        assert_eq!(solution.puzzle().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_max_steps() {
+        let puzzle =
+            Puzzle::from_str("050703060007000800000816000000030000005000100730040086906000204840572093000409000")
+                .unwrap();
+        let limits = Limits { max_steps: Some(10), ..Limits::default() };
+        let mut solution = BaseSolution::new(puzzle).with_limits(limits);
+        assert_eq!(Backtracking.solve(&mut solution, None).unwrap(), Outcome::GaveUp(Limit::MaxSteps));
+
+        // The limit is only checked between squares, so it can be overshot by a few
+        // tries.
+        assert!((10..20).contains(&solution.steps()));
+        assert!(!solution.puzzle().is_filled_out());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let puzzle =
+            Puzzle::from_str("050703060007000800000816000000030000005000100730040086906000204840572093000409000")
+                .unwrap();
+        let mut solution = BaseSolution::new(puzzle.clone());
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert_eq!(Backtracking.solve(&mut solution, Some(&cancel)).unwrap(), Outcome::Cancelled);
+        assert_eq!(*solution.puzzle(), puzzle);
+    }
+
+    #[test]
+    fn test_progress() {
+        let puzzle =
+            Puzzle::from_str("050703060007000800000816000000030000005000100730040086906000204840572093000409000")
+                .unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let observed = Arc::clone(&reports);
+        let mut solution = BaseSolution::new(puzzle)
+            .with_observer(Duration::ZERO, move |progress| observed.lock().unwrap().push(*progress));
+        Backtracking.solve(&mut solution, None).unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert!(!reports.is_empty());
+        assert!(reports.windows(2).all(|pair| pair[0].steps <= pair[1].steps));
+        assert!(reports.iter().all(|progress| progress.depth < 51 && progress.steps < solution.steps()));
     }
 }
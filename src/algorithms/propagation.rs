@@ -0,0 +1,182 @@
+use anyhow::{Result, anyhow};
+
+use crate::algorithms::{Algorithm, Outcome};
+use crate::cancel::CancellationToken;
+use crate::solution::Solution;
+
+/// Depth-first search which narrows down the puzzle before each guess.
+///
+/// Before guessing, every square with only a single candidate left (a "naked
+/// single") is filled in, repeating until no more can be found. Guesses are
+/// then made in the square with the fewest candidates. This typically needs
+/// orders of magnitude fewer edits than [`Backtracking`](super::Backtracking).
+pub struct ConstraintPropagation;
+
+impl Algorithm for ConstraintPropagation {
+    fn solve<T: Solution>(&self, solution: &mut T, cancel: Option<&CancellationToken>) -> Result<Outcome> {
+        solution.base().check_puzzle()?;
+        match search(solution, cancel, 0)? {
+            Search::Solved => Ok(Outcome::Solved),
+            Search::Stopped(outcome) => Ok(outcome),
+            Search::Contradiction => Err(anyhow!("puzzle has no solution")),
+        }
+    }
+}
+
+enum Search {
+    Solved,
+    Contradiction,
+    /// The search was cancelled or gave up before finishing.
+    Stopped(Outcome),
+}
+
+/// Fill in the singles and then guess, `depth` guesses deep.
+fn search<T: Solution>(solution: &mut T, cancel: Option<&CancellationToken>, depth: usize) -> Result<Search> {
+    // Squares we fill in at this depth, which need clearing again if we turn out to
+    // be on the wrong path.
+    let mut filled = Vec::new();
+
+    let guess = loop {
+        // A single guess can lead to many passes over the puzzle filling in singles,
+        // so check whether to stop before each one rather than only once per guess.
+        if let Some(outcome) = stop(solution, cancel) {
+            return Ok(Search::Stopped(outcome));
+        }
+        solution.base().report_progress(depth, cancel);
+
+        let empty: Vec<_> =
+            solution.base().iter_puzzle().filter(|(_, digit)| digit.is_none()).map(|(idx, _)| idx).collect();
+
+        let mut fewest: Option<(usize, Vec<u8>)> = None;
+        let mut singles = Vec::new();
+        for idx in empty {
+            let candidates = solution.base().candidates(idx);
+            match candidates.as_slice() {
+                [] => {
+                    undo(solution, &filled)?;
+                    return Ok(Search::Contradiction);
+                }
+                [digit] => singles.push((idx, *digit)),
+                _ => {
+                    if fewest.as_ref().is_none_or(|(_, fewest)| candidates.len() < fewest.len()) {
+                        fewest = Some((idx, candidates));
+                    }
+                }
+            }
+        }
+
+        if singles.is_empty() {
+            break fewest;
+        }
+
+        for (idx, digit) in singles {
+            // Filling in one single can take away the only candidate of another, so
+            // check again before each one.
+            solution.set_with_reason(idx, Some(digit), "single")?;
+            filled.push(idx);
+            if !solution.base().is_valid_digit(idx) {
+                undo(solution, &filled)?;
+                return Ok(Search::Contradiction);
+            }
+        }
+    };
+
+    let Some((idx, candidates)) = guess else {
+        return Ok(Search::Solved);
+    };
+
+    for candidate in candidates {
+        solution.set_with_reason(idx, Some(candidate), "guess")?;
+        match search(solution, cancel, depth + 1)? {
+            Search::Contradiction => {}
+            search => return Ok(search),
+        }
+    }
+
+    solution.set_with_reason(idx, None, "backtrack")?;
+    undo(solution, &filled)?;
+    Ok(Search::Contradiction)
+}
+
+/// Why the search should stop early, if it should.
+fn stop<T: Solution>(solution: &mut T, cancel: Option<&CancellationToken>) -> Option<Outcome> {
+    if cancel.is_some_and(CancellationToken::is_cancelled) {
+        return Some(Outcome::Cancelled);
+    }
+    solution.base().limit_reached().map(Outcome::GaveUp)
+}
+
+fn undo<T: Solution>(solution: &mut T, filled: &[usize]) -> Result<()> {
+    for &idx in filled.iter().rev() {
+        solution.set_with_reason(idx, None, "backtrack")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::algorithms::{Limit, Limits};
+    use crate::puzzle::Puzzle;
+    use crate::solution::base::BaseSolution;
+
+    #[test]
+    fn test_solve() {
+        let puzzle =
+            Puzzle::from_str("050703060007000800000816000000030000005000100730040086906000204840572093000409000")
+                .unwrap();
+        let mut solution = BaseSolution::new(puzzle);
+        ConstraintPropagation.solve(&mut solution, None).unwrap();
+
+        let expected =
+            Puzzle::from_str("158723469367954821294816375619238547485697132732145986976381254841572693523469718")
+                .unwrap();
+
+        assert_eq!(*solution.puzzle(), expected);
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        // The first row needs a 9, but the last column already has one.
+        let puzzle =
+            Puzzle::from_str("123456780000000009000000000000000000000000000000000000000000000000000000000000000")
+                .unwrap();
+        let mut solution = BaseSolution::new(puzzle);
+        assert!(ConstraintPropagation.solve(&mut solution, None).is_err());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let puzzle = Puzzle::from_str(&".".repeat(256)).unwrap();
+        let limits = Limits { timeout: Some(Duration::ZERO), ..Limits::default() };
+        let mut solution = BaseSolution::new(puzzle).with_limits(limits);
+        assert_eq!(ConstraintPropagation.solve(&mut solution, None).unwrap(), Outcome::GaveUp(Limit::Timeout));
+        assert_eq!(solution.steps(), 0);
+    }
+
+    #[test]
+    fn test_solve_killer() {
+        // The grid starts out empty, so only the cages stop any old solution from
+        // being valid.
+        let puzzle = Puzzle::from_str(
+            "................
+             cage 4 r1c1 r2c1
+             cage 5 r1c2 r1c3
+             cage 6 r1c4 r2c4
+             cage 5 r2c2 r2c3
+             cage 6 r3c1 r4c1
+             cage 5 r3c2 r3c3
+             cage 4 r3c4 r4c4
+             cage 5 r4c2 r4c3",
+        )
+        .unwrap();
+        let mut solution = BaseSolution::new(puzzle);
+        ConstraintPropagation.solve(&mut solution, None).unwrap();
+
+        assert!(solution.puzzle().is_filled_out());
+        assert_eq!(solution.puzzle().validate(), Ok(()));
+    }
+}
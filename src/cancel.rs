@@ -0,0 +1,124 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::solution::progress::{Observer, Progress};
+
+/// A handle for stopping a solve from elsewhere, such as another thread or the
+/// TUI when the user quits, and for watching its progress.
+///
+/// Clones share the same state, so cancelling any one of them cancels them
+/// all, and one token can stop any number of solvers at once. Checking it is
+/// cheap enough for solvers to do on every step.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    /// When the token cancels itself, if ever.
+    deadline: Option<Instant>,
+    /// Told the progress of solves using the token, see
+    /// [`CancellationToken::on_progress`].
+    observer: Mutex<Option<Observer>>,
+    /// Whether `observer` has been set, so that solves needn't lock it to
+    /// find out.
+    observed: AtomicBool,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token which is cancelled once `deadline` has passed, if not before.
+    ///
+    /// Unlike [`Limits::timeout`](crate::Limits::timeout), which gives each
+    /// solve its own time limit, the deadline is shared by everything holding
+    /// the token, e.g. a whole batch of solves.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self { inner: Arc::new(Inner { deadline: Some(deadline), ..Inner::default() }) }
+    }
+
+    /// A token which is cancelled once `timeout` has passed from now, if not
+    /// before.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token has been cancelled or its deadline has
+    /// passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+            || self.inner.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Call `callback` with the [`Progress`] of solves using this token every
+    /// `interval` or so, replacing any callback set before.
+    ///
+    /// This works like
+    /// [`BaseSolution::with_observer`](crate::BaseSolution::with_observer),
+    /// but can be set up by whoever holds the token rather than whoever
+    /// creates the solution. If several solves share the token, the callback
+    /// hears from whichever of them is due to report.
+    pub fn on_progress(&self, interval: Duration, callback: impl FnMut(&Progress) + Send + 'static) {
+        *self.inner.observer.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) =
+            Some(Observer::new(interval, callback));
+        self.inner.observed.store(true, Ordering::Release);
+    }
+
+    /// Let the progress callback know how far a solve has got, if there is
+    /// one and it's due to be told.
+    pub(crate) fn report_progress(&self, steps: u64, depth: usize) {
+        if !self.inner.observed.load(Ordering::Acquire) {
+            return;
+        }
+        // Another solve sharing the token is already reporting, so this one needn't.
+        if let Ok(mut observer) = self.inner.observer.try_lock()
+            && let Some(observer) = observer.as_mut()
+        {
+            observer.observe(steps, depth);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert!(clone.is_cancelled());
+
+        assert!(CancellationToken::with_timeout(Duration::ZERO).is_cancelled());
+        assert!(!CancellationToken::with_timeout(Duration::from_secs(60)).is_cancelled());
+    }
+
+    #[test]
+    fn test_on_progress() {
+        let token = CancellationToken::new();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        token.clone().on_progress(Duration::ZERO, move |progress| sink.lock().unwrap().push(*progress));
+
+        // The clock is only checked once every thousand or so steps.
+        token.report_progress(1, 0);
+        token.report_progress(1024, 1);
+        token.report_progress(1500, 2);
+        token.report_progress(2048, 3);
+        let steps: Vec<_> = reports.lock().unwrap().iter().map(|progress| progress.steps).collect();
+        assert_eq!(steps, [1024, 2048]);
+    }
+}
@@ -0,0 +1,354 @@
+//! Printable vector drawings of puzzles, which can be written as SVG or PDF.
+//!
+//! Puzzles are first drawn as [`Shape`]s on [`Page`]s, measured in points
+//! from the top-left corner, which each output format then writes out.
+
+use crate::format::Entry;
+use crate::puzzle::constraint::Windoku;
+use crate::puzzle::{Puzzle, Variant};
+use crate::solution::base::BaseSolution;
+use crate::util::DigitChar;
+
+pub mod pdf;
+pub mod svg;
+
+/// The size of an A4 page, in points.
+pub const A4: (f32, f32) = (595.0, 842.0);
+
+const MARGIN: f32 = 36.0;
+/// The size of a puzzle drawn on its own.
+const GRID_SIZE: f32 = 360.0;
+/// The height of the title above each puzzle, relative to the size of the
+/// puzzle.
+const TITLE_SCALE: f32 = 0.06;
+
+/// Grays, from 0 for black to 1 for white.
+const BLACK: f32 = 0.0;
+const FILLED_GRAY: f32 = 0.35;
+const CAGE_GRAY: f32 = 0.4;
+const DIAGONAL_GRAY: f32 = 0.6;
+const SHADE_GRAY: f32 = 0.88;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page {
+    pub width: f32,
+    pub height: f32,
+    pub shapes: Vec<Shape>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        gray: f32,
+        dashed: bool,
+    },
+    /// A filled rectangle.
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        gray: f32,
+    },
+    /// Text whose baseline starts at, or is centered on, (`x`, `y`).
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        bold: bool,
+        gray: f32,
+        anchor: Anchor,
+        text: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Anchor {
+    Start,
+    Middle,
+}
+
+/// What to draw in the squares which aren't given.
+#[derive(Clone, Copy, Debug)]
+pub enum Fill<'a> {
+    Empty,
+    /// The digits of this solution, lighter than the givens.
+    Solution(&'a Puzzle),
+    /// The digits each square could hold, penciled in.
+    Candidates,
+}
+
+impl Page {
+    pub fn new((width, height): (f32, f32)) -> Self {
+        Self { width, height, shapes: Vec::new() }
+    }
+
+    /// A page just big enough for a single puzzle and its title.
+    pub fn single(entry: &Entry, fill: Fill) -> Self {
+        let title_height = GRID_SIZE * TITLE_SCALE;
+        let mut page = Self::new((GRID_SIZE + 2.0 * MARGIN, GRID_SIZE + title_height + 2.0 * MARGIN));
+        page.draw_titled(entry.metadata.title.as_deref(), &entry.puzzle, fill, (MARGIN, MARGIN), GRID_SIZE);
+        page
+    }
+
+    /// Draw the puzzle, `size` points across, below its title.
+    fn draw_titled(&mut self, title: Option<&str>, puzzle: &Puzzle, fill: Fill, (x, y): (f32, f32), size: f32) {
+        let title_size = size * TITLE_SCALE;
+        if let Some(title) = title {
+            self.shapes.push(Shape::Text {
+                x,
+                y: y + title_size * 0.75,
+                size: title_size * 0.75,
+                bold: true,
+                gray: BLACK,
+                anchor: Anchor::Start,
+                text: title.to_string(),
+            });
+        }
+        self.draw_puzzle(puzzle, fill, (x, y + title_size), size);
+    }
+
+    /// Draw the puzzle's grid with its top-left corner at (`x`, `y`).
+    pub fn draw_puzzle(&mut self, puzzle: &Puzzle, fill: Fill, (x, y): (f32, f32), size: f32) {
+        let dimensions = puzzle.dimensions;
+        let squares = dimensions.size();
+        let cell = size / squares as f32;
+        let corner = |index: usize| {
+            let (row, col) = dimensions.position(index);
+            (x + col as f32 * cell, y + row as f32 * cell)
+        };
+
+        // Shading goes first so that everything else is drawn over it.
+        if puzzle.variants.contains(&Variant::Windoku) {
+            for index in Windoku::boxes(dimensions).into_iter().flatten() {
+                let (left, top) = corner(index);
+                self.shapes.push(Shape::Rect { x: left, y: top, width: cell, height: cell, gray: SHADE_GRAY });
+            }
+        }
+        if puzzle.variants.contains(&Variant::Diagonal) {
+            let width = size / 500.0;
+            for (from, to) in [((x, y), (x + size, y + size)), ((x + size, y), (x, y + size))] {
+                self.shapes.push(Shape::Line { from, to, width, gray: DIAGONAL_GRAY, dashed: false });
+            }
+        }
+
+        self.draw_cages(puzzle, (x, y), cell);
+        self.draw_digits(puzzle, fill, (x, y), cell);
+
+        // Lines are heavy where they separate regions, which includes the edges of the grid.
+        let thin = size / 600.0;
+        let thick = size / 150.0;
+        let region = |row: usize, col: usize| {
+            (row < squares && col < squares).then(|| puzzle.regions.id(dimensions.index(row, col)))
+        };
+        for line in 0..=squares {
+            for n in 0..squares {
+                let (before, after) = (line.checked_sub(1), line);
+                let across = before.and_then(|row| region(row, n)) != region(after, n) || before.is_none();
+                let down = before.and_then(|col| region(n, col)) != region(n, after) || before.is_none();
+                let at = line as f32 * cell;
+                let (start, end) = (n as f32 * cell, (n + 1) as f32 * cell);
+                for (heavy, from, to) in
+                    [(across, (x + start, y + at), (x + end, y + at)), (down, (x + at, y + start), (x + at, y + end))]
+                {
+                    let width = if heavy { thick } else { thin };
+                    self.shapes.push(Shape::Line { from, to, width, gray: BLACK, dashed: false });
+                }
+            }
+        }
+    }
+
+    /// Outline each cage with a dashed line just inside it, with its sum in its
+    /// top-left corner.
+    fn draw_cages(&mut self, puzzle: &Puzzle, (x, y): (f32, f32), cell: f32) {
+        let dimensions = puzzle.dimensions;
+        let size = dimensions.size() as isize;
+        let inset = cell * 0.08;
+        let width = cell / 60.0;
+        for cage in &puzzle.cages {
+            let contains = |row: isize, col: isize| {
+                (0..size).contains(&row)
+                    && (0..size).contains(&col)
+                    && cage.squares.contains(&dimensions.index(row as usize, col as usize))
+            };
+            for &index in &cage.squares {
+                let (row, col) = dimensions.position(index);
+                let (row, col) = (row as isize, col as isize);
+                let (left, top) = (x + col as f32 * cell, y + row as f32 * cell);
+                let (right, bottom) = (left + cell, top + cell);
+                // Each side is outlined unless the cage carries on past it, and the outline
+                // runs up to the edge of the square where it carries on along the cage.
+                let start = |inside: bool, edge: f32| if inside { edge } else { edge + inset };
+                let end = |inside: bool, edge: f32| if inside { edge } else { edge - inset };
+                let (up, down) = (contains(row - 1, col), contains(row + 1, col));
+                let (back, ahead) = (contains(row, col - 1), contains(row, col + 1));
+                let sides = [
+                    (up, (start(back, left), top + inset), (end(ahead, right), top + inset)),
+                    (down, (start(back, left), bottom - inset), (end(ahead, right), bottom - inset)),
+                    (back, (left + inset, start(up, top)), (left + inset, end(down, bottom))),
+                    (ahead, (right - inset, start(up, top)), (right - inset, end(down, bottom))),
+                ];
+                for (inside, from, to) in sides {
+                    if !inside {
+                        self.shapes.push(Shape::Line { from, to, width, gray: CAGE_GRAY, dashed: true });
+                    }
+                }
+            }
+
+            let (row, col) = dimensions.position(cage.first());
+            let size = cell * 0.22;
+            self.shapes.push(Shape::Text {
+                x: x + col as f32 * cell + inset * 1.5,
+                y: y + row as f32 * cell + inset * 1.5 + size * 0.75,
+                size,
+                bold: false,
+                gray: BLACK,
+                anchor: Anchor::Start,
+                text: cage.sum.to_string(),
+            });
+        }
+    }
+
+    fn draw_digits(&mut self, puzzle: &Puzzle, fill: Fill, (x, y): (f32, f32), cell: f32) {
+        let dimensions = puzzle.dimensions;
+        let mut candidates = matches!(fill, Fill::Candidates).then(|| BaseSolution::new(puzzle.clone()));
+        for index in 0..dimensions.squares() {
+            let (row, col) = dimensions.position(index);
+            let (left, top) = (x + col as f32 * cell, y + row as f32 * cell);
+            let (digit, bold) = match (puzzle.get(index), fill) {
+                (Some(digit), _) => {
+                    (Some(digit), puzzle.initially_filled.is_none() || puzzle.is_initially_filled(index))
+                }
+                (None, Fill::Solution(solution)) => (solution.get(index), false),
+                (None, _) => (None, false),
+            };
+            if let Some(digit) = digit {
+                let size = cell * 0.6;
+                self.push_digit(digit, (left + cell / 2.0, top + cell / 2.0), size, bold);
+                continue;
+            }
+
+            // Candidates are arranged like the squares of a box, one for each digit.
+            let Some(solution) = &mut candidates else {
+                continue;
+            };
+            let (rows, cols) = (dimensions.box_rows, dimensions.box_cols);
+            let (width, height) = (cell / cols as f32, cell / rows as f32);
+            for digit in solution.candidates(index) {
+                let (row, col) = ((digit as usize - 1) / cols, (digit as usize - 1) % cols);
+                let center = (left + (col as f32 + 0.5) * width, top + (row as f32 + 0.5) * height);
+                self.push_digit(digit, center, width.min(height) * 0.7, false);
+            }
+        }
+    }
+
+    /// Write a digit centered on `center`, bold if it was given.
+    fn push_digit(&mut self, digit: u8, (x, y): (f32, f32), size: f32, bold: bool) {
+        // Digits are only ever as high as the puzzle size, which is at most 25, so this
+        // unwrap is safe.
+        let text = digit.digit_char().unwrap().to_string();
+        self.shapes.push(Shape::Text {
+            x,
+            // Digits and capital letters are about 0.7em tall, so this centers them vertically.
+            y: y + size * 0.35,
+            size,
+            bold,
+            gray: if bold { BLACK } else { FILLED_GRAY },
+            anchor: Anchor::Middle,
+            text,
+        });
+    }
+}
+
+/// Lay the puzzles out on A4 pages, `per_page` to a page, followed by pages
+/// of their solutions laid out the same way.
+pub fn booklet(entries: &[Entry], solutions: &[Puzzle], per_page: usize) -> Vec<Page> {
+    let per_page = per_page.max(1);
+    let cols = (per_page as f32).sqrt().ceil() as usize;
+    let rows = per_page.div_ceil(cols);
+    let (page_width, page_height) = A4;
+    let (slot_width, slot_height) =
+        ((page_width - 2.0 * MARGIN) / cols as f32, (page_height - 2.0 * MARGIN) / rows as f32);
+    // Each puzzle is drawn as big as fits with its title and a gap around it.
+    let gap = MARGIN / 2.0;
+    let size = (slot_width - gap).min((slot_height - gap) / (1.0 + TITLE_SCALE));
+
+    let mut pages = Vec::new();
+    let puzzles = entries.iter().enumerate().map(|(n, entry)| {
+        let title = match &entry.metadata.title {
+            Some(title) => format!("{}. {title}", n + 1),
+            None => format!("Puzzle {}", n + 1),
+        };
+        (title, &entry.puzzle, Fill::Empty)
+    });
+    let solutions = solutions.iter().enumerate().map(|(n, solution)| {
+        let puzzle = &entries[n].puzzle;
+        (format!("Solution {}", n + 1), puzzle, Fill::Solution(solution))
+    });
+    for section in [puzzles.collect::<Vec<_>>(), solutions.collect()] {
+        for chunk in section.chunks(per_page) {
+            let mut page = Page::new(A4);
+            for (n, (title, puzzle, fill)) in chunk.iter().enumerate() {
+                let (row, col) = (n / cols, n % cols);
+                // Center each puzzle in its slot.
+                let x = MARGIN + col as f32 * slot_width + (slot_width - size) / 2.0;
+                let y = MARGIN + row as f32 * slot_height + (slot_height - size * (1.0 + TITLE_SCALE)) / 2.0;
+                page.draw_titled(Some(title), puzzle, *fill, (x, y), size);
+            }
+            pages.push(page);
+        }
+    }
+    pages
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn texts(page: &Page) -> Vec<&str> {
+        let texts = page.shapes.iter().filter_map(|shape| match shape {
+            Shape::Text { text, .. } => Some(text.as_str()),
+            _ => None,
+        });
+        texts.collect()
+    }
+
+    #[test]
+    fn test_single() {
+        let puzzle = Puzzle::from_str("1.3.....2..3...1").unwrap();
+        let solution = Puzzle::from_str("1234341221434321").unwrap();
+        let mut entry = Entry::new(puzzle);
+        entry.metadata.title = Some("Tiny".to_string());
+
+        let page = Page::single(&entry, Fill::Empty);
+        assert_eq!(texts(&page), ["Tiny", "1", "3", "2", "3", "1"]);
+        // Each of the 5 lines across and down is split into 4 segments.
+        let lines = page.shapes.iter().filter(|shape| matches!(shape, Shape::Line { .. }));
+        assert_eq!(lines.count(), 2 * 5 * 4);
+
+        let page = Page::single(&entry, Fill::Solution(&solution));
+        assert_eq!(texts(&page).concat(), "Tiny1234341221434321");
+
+        // An untitled puzzle with no digits has no text at all.
+        assert!(texts(&Page::single(&Entry::default(), Fill::Empty)).is_empty());
+
+        // r1c2 and r1c4 can both only be 2 or 4.
+        let page = Page::single(&entry, Fill::Candidates);
+        assert!(texts(&page).concat().starts_with("Tiny124324"));
+    }
+
+    #[test]
+    fn test_booklet() {
+        let entries = vec![Entry::new(Puzzle::from_str("1.3.....2..3...1").unwrap()); 5];
+        let solutions = vec![Puzzle::from_str("1234341221434321").unwrap(); 5];
+        let pages = booklet(&entries, &solutions, 4);
+        assert_eq!(pages.len(), 4);
+        assert_eq!(texts(&pages[1])[0], "Puzzle 5");
+        assert_eq!(texts(&pages[2])[0], "Solution 1");
+    }
+}
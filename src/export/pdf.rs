@@ -0,0 +1,181 @@
+//! A minimal PDF writer, using the standard Helvetica fonts which every PDF
+//! reader has, so nothing needs to be embedded.
+
+use std::fmt::Write;
+
+use crate::export::{Anchor, Page, Shape};
+
+/// The objects which come before the pages: the catalog, the page tree and the
+/// two fonts.
+const FIXED_OBJECTS: usize = 4;
+
+/// Write the pages as a PDF document.
+pub fn write(pages: &[Page]) -> Vec<u8> {
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        String::new(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+    let mut kids = Vec::new();
+    for page in pages {
+        // Each page is followed by its contents.
+        let number = objects.len() + 1;
+        kids.push(format!("{number} 0 R"));
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> \
+             /Contents {} 0 R >>",
+            number_text(page.width),
+            number_text(page.height),
+            number + 1
+        ));
+        let contents = contents(page);
+        objects.push(format!("<< /Length {} >>\nstream\n{contents}endstream", contents.len()));
+    }
+    objects[1] = format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len());
+    debug_assert_eq!(objects.len(), FIXED_OBJECTS + 2 * pages.len());
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (n, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf += &format!("{} 0 obj\n{object}\nendobj\n", n + 1);
+    }
+    let xref = pdf.len();
+    pdf += &format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        pdf += &format!("{offset:010} 00000 n \n");
+    }
+    pdf += &format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n", objects.len() + 1);
+    pdf.into_bytes()
+}
+
+/// The drawing operators for the page. PDF measures up from the bottom of the
+/// page, so `y`s are flipped.
+fn contents(page: &Page) -> String {
+    let flip = |y: f32| number_text(page.height - y);
+    // Lines end in squares, so the lines around a square meet at its corners.
+    let mut contents = String::from("2 J\n");
+    for shape in &page.shapes {
+        // Writing to a String never fails.
+        let _ = match shape {
+            Shape::Line { from, to, width, gray, dashed } => {
+                let dash = if *dashed {
+                    format!("[{} {}] 0 d", number_text(width * 4.0), number_text(width * 3.0))
+                } else {
+                    "[] 0 d".to_string()
+                };
+                writeln!(
+                    contents,
+                    "{} G {} w {dash} {} {} m {} {} l S",
+                    number_text(*gray),
+                    number_text(*width),
+                    number_text(from.0),
+                    flip(from.1),
+                    number_text(to.0),
+                    flip(to.1)
+                )
+            }
+            Shape::Rect { x, y, width, height, gray } => writeln!(
+                contents,
+                "{} g {} {} {} {} re f",
+                number_text(*gray),
+                number_text(*x),
+                flip(y + height),
+                number_text(*width),
+                number_text(*height)
+            ),
+            Shape::Text { x, y, size, bold, gray, anchor, text } => {
+                let x = match anchor {
+                    Anchor::Start => *x,
+                    Anchor::Middle => x - text_width(text, *bold) * size / 2.0,
+                };
+                let font = if *bold { "F2" } else { "F1" };
+                writeln!(
+                    contents,
+                    "{} g BT /{font} {} Tf {} {} Td ({}) Tj ET",
+                    number_text(*gray),
+                    number_text(*size),
+                    number_text(x),
+                    flip(*y),
+                    escape(text)
+                )
+            }
+        };
+    }
+    contents
+}
+
+/// Write a number with no more precision than anyone can see.
+fn number_text(number: f32) -> String {
+    let text = format!("{number:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// The width of the text in ems, which is only exact for the digits and
+/// letters used for digits.
+fn text_width(text: &str, bold: bool) -> f32 {
+    const REGULAR: [u16; 16] = [667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667];
+    const BOLD: [u16; 16] = [722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667];
+    let widths = if bold { &BOLD } else { &REGULAR };
+    let width = |char: char| match char {
+        'A'..='P' => widths[char as usize - 'A' as usize],
+        // Helvetica's digits are all the same width, and most letters are about that wide too.
+        _ => 556,
+    };
+    text.chars().map(width).map(f32::from).sum::<f32>() / 1000.0
+}
+
+/// Escape text for a PDF string, in the Windows-1252 encoding given to the
+/// fonts, which matches Unicode for the Latin-1 characters.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for char in text.chars() {
+        match char {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            ' '..='~' => escaped.push(char),
+            '\u{a0}'..='\u{ff}' => escaped += &format!("\\{:03o}", char as u32),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::export::booklet;
+    use crate::format::Entry;
+    use crate::puzzle::Puzzle;
+
+    #[test]
+    fn test_write() {
+        let entries = vec![Entry::new(Puzzle::from_str("1.3.....2..3...1").unwrap()); 3];
+        let solutions = vec![Puzzle::from_str("1234341221434321").unwrap(); 3];
+        let pdf = String::from_utf8(write(&booklet(&entries, &solutions, 2))).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains("/Count 4"));
+        assert!(pdf.contains("(Puzzle 3) Tj"));
+
+        // Every entry in the cross-reference table points at its object.
+        let xref = pdf.rfind("\nxref\n").unwrap() + 1;
+        for (n, line) in pdf[xref..].lines().skip(3).take_while(|line| line.ends_with(" n ")).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", n + 1)));
+        }
+        assert!(pdf.ends_with(&format!("startxref\n{xref}\n%%EOF\n")));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("(Café) \\ ☃"), "\\(Caf\\351\\) \\\\ ?");
+        assert_eq!(number_text(12.5), "12.5");
+        assert_eq!(number_text(3.0), "3");
+        assert_eq!(text_width("1", false), 0.556);
+    }
+}
@@ -0,0 +1,89 @@
+use std::fmt::Write;
+
+use crate::export::{Anchor, Page, Shape};
+
+/// Write the page as an SVG image, measured in points.
+pub fn write(page: &Page) -> String {
+    let Page { width, height, shapes } = page;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}pt\" height=\"{height}pt\" \
+         viewBox=\"0 0 {width} {height}\">\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n"
+    );
+    for shape in shapes {
+        // Writing to a String never fails.
+        let _ = match shape {
+            Shape::Line { from, to, width, gray, dashed } => {
+                let dash = if *dashed {
+                    format!(" stroke-dasharray=\"{} {}\"", width * 4.0, width * 3.0)
+                } else {
+                    String::new()
+                };
+                writeln!(
+                    svg,
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{width}\" \
+                     stroke-linecap=\"square\"{dash}/>",
+                    from.0,
+                    from.1,
+                    to.0,
+                    to.1,
+                    color(*gray)
+                )
+            }
+            Shape::Rect { x, y, width, height, gray } => {
+                writeln!(
+                    svg,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" fill=\"{}\"/>",
+                    color(*gray)
+                )
+            }
+            Shape::Text { x, y, size, bold, gray, anchor, text } => {
+                let anchor = match anchor {
+                    Anchor::Start => "start",
+                    Anchor::Middle => "middle",
+                };
+                let weight = if *bold { "bold" } else { "normal" };
+                writeln!(
+                    svg,
+                    "<text x=\"{x}\" y=\"{y}\" font-family=\"Helvetica, Arial, sans-serif\" font-size=\"{size}\" \
+                     font-weight=\"{weight}\" fill=\"{}\" text-anchor=\"{anchor}\">{}</text>",
+                    color(*gray),
+                    escape(text)
+                )
+            }
+        };
+    }
+    svg += "</svg>\n";
+    svg
+}
+
+/// The CSS color for a gray level from 0 for black to 1 for white.
+fn color(gray: f32) -> String {
+    let level = (gray.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{level:02x}{level:02x}{level:02x}")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::export::Fill;
+    use crate::format::Entry;
+    use crate::puzzle::Puzzle;
+
+    #[test]
+    fn test_write() {
+        let mut entry = Entry::new(Puzzle::from_str("1.3.....2..4...1\ncage 3 r1c1 r1c2").unwrap());
+        entry.metadata.title = Some("Cats & dogs".to_string());
+        let svg = write(&Page::single(&entry, Fill::Empty));
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"432pt\""));
+        assert!(svg.contains(">Cats &amp; dogs</text>"));
+        assert!(svg.contains("font-weight=\"bold\" fill=\"#000000\" text-anchor=\"middle\">1</text>"));
+        assert!(svg.contains("stroke-dasharray"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::puzzle::{Dimensions, Puzzle};
+
+pub mod json;
+pub mod opensudoku;
+pub mod sadman;
+pub mod simple_sudoku;
+
+/// A file format that puzzles can be read from and written to.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Format {
+    /// The puzzle's grid on one line or a line per row, followed by its other
+    /// rules
+    #[default]
+    Text,
+    /// SadMan Software's single puzzle format, with metadata
+    Sdk,
+    /// SadMan Software's collection format, with a puzzle on each line
+    Sdm,
+    /// Simple Sudoku's grid, split into boxes
+    Ss,
+    /// OpenSudoku's XML collection format, with metadata
+    #[cfg_attr(feature = "cli", value(name = "opensudoku"))]
+    OpenSudoku,
+    /// A JSON document with the puzzle's rules and metadata, see
+    /// [`json::Document`]
+    Json,
+}
+
+/// Details about a puzzle which the formats that have them carry along with
+/// it.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Metadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<String>,
+    /// Where the puzzle was published.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl Metadata {
+    /// Returns true if none of the details are known.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A puzzle read from, or to be written to, a file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Entry {
+    pub puzzle: Puzzle,
+    pub metadata: Metadata,
+}
+
+impl Entry {
+    pub fn new(puzzle: Puzzle) -> Self {
+        Self { puzzle, metadata: Metadata::default() }
+    }
+}
+
+impl Format {
+    /// Every format, e.g. for trying each of them in turn.
+    pub const ALL: [Format; 6] = [Self::Text, Self::Sdk, Self::Sdm, Self::Ss, Self::OpenSudoku, Self::Json];
+
+    /// The format a file is in, going by its extension if it has a known one,
+    /// and its contents otherwise.
+    pub fn detect(path: Option<&Path>, text: &str) -> Self {
+        path.and_then(Self::from_extension).unwrap_or_else(|| Self::from_contents(text))
+    }
+
+    /// The format usually given the extension of the file at `path`, if any.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "sdk" => Some(Self::Sdk),
+            "sdm" => Some(Self::Sdm),
+            "ss" => Some(Self::Ss),
+            "opensudoku" | "xml" => Some(Self::OpenSudoku),
+            "json" => Some(Self::Json),
+            "txt" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of `text`, falling back to [`Format::Text`], which
+    /// also reads Simple Sudoku grids.
+    pub fn from_contents(text: &str) -> Self {
+        let lines: Vec<_> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        if lines.first().is_some_and(|line| line.starts_with('<')) {
+            Self::OpenSudoku
+        } else if lines.first().is_some_and(|line| line.starts_with(['{', '['])) {
+            Self::Json
+        } else if lines.iter().any(|line| sadman::is_sdk_line(line)) {
+            Self::Sdk
+        } else if lines.len() > 1 && lines.iter().all(|line| sadman::is_sdm_line(line)) {
+            Self::Sdm
+        } else {
+            Self::Text
+        }
+    }
+
+    /// Parse every puzzle in `text`.
+    pub fn parse(self, text: &str) -> Result<Vec<Entry>> {
+        match self {
+            Self::Text | Self::Ss => Ok(vec![Entry::new(Puzzle::from_str(text)?)]),
+            Self::Sdk => Ok(vec![sadman::parse_sdk(text)?]),
+            Self::Sdm => sadman::parse_sdm(text),
+            Self::OpenSudoku => opensudoku::parse(text),
+            Self::Json => json::parse(text),
+        }
+    }
+
+    /// Write the puzzles in this format, failing if the format can't hold them.
+    pub fn write(self, entries: &[Entry]) -> Result<String> {
+        if !matches!(self, Self::Text | Self::Json) && !entries.iter().all(|entry| is_classic(&entry.puzzle)) {
+            return Err(anyhow!("{self} files only hold 9x9 puzzles without extra rules"));
+        }
+
+        match (self, entries) {
+            (Self::Sdm, entries) => Ok(sadman::write_sdm(entries)),
+            (Self::OpenSudoku, entries) => opensudoku::write(entries),
+            (Self::Json, entries) => json::write(entries),
+            (Self::Text, [entry]) => {
+                let mut lines = vec![entry.puzzle.serialize()];
+                lines.extend(entry.puzzle.serialize_rules());
+                Ok(lines.into_iter().map(|line| line + "\n").collect())
+            }
+            (Self::Sdk, [entry]) => Ok(sadman::write_sdk(entry)),
+            (Self::Ss, [entry]) => Ok(simple_sudoku::write(&entry.puzzle)),
+            (_, entries) => Err(anyhow!("{self} files hold a single puzzle, not {}", entries.len())),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Text => "text",
+            Self::Sdk => "sdk",
+            Self::Sdm => "sdm",
+            Self::Ss => "ss",
+            Self::OpenSudoku => "opensudoku",
+            Self::Json => "json",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Returns true if the puzzle is a standard 9x9 Sudoku, which is all that the
+/// formats written by other programs support.
+fn is_classic(puzzle: &Puzzle) -> bool {
+    puzzle.dimensions == Dimensions::STANDARD && puzzle.serialize_rules().is_empty()
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+    use proptest::sample;
+
+    use super::*;
+
+    const PUZZLE: &str = "050703060007000800000816000000030000005000100730040086906000204840572093000409000";
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::detect(Some(Path::new("puzzles/EASY.SDM")), ""), Format::Sdm);
+        assert_eq!(Format::detect(Some(Path::new("puzzle")), "#AAnonymous\n"), Format::Sdk);
+        assert_eq!(Format::detect(None, &format!("{PUZZLE}\n{PUZZLE}\n")), Format::Sdm);
+        assert_eq!(Format::detect(None, "[{\"grid\": \"\"}]"), Format::Json);
+        assert_eq!(Format::detect(None, "<?xml version=\"1.0\"?>\n<opensudoku/>"), Format::OpenSudoku);
+        assert_eq!(Format::detect(None, &format!("# A comment\n{PUZZLE}\n")), Format::Text);
+        assert_eq!(Format::detect(None, &format!("{PUZZLE}\nvariant diagonal")), Format::Text);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut entry = Entry::new(Puzzle::from_str(PUZZLE).unwrap());
+        entry.metadata.title = Some("Easy <1>".to_string());
+        entry.metadata.author = Some("Anonymous".to_string());
+        entry.metadata.difficulty = Some("Easy".to_string());
+        let entries = vec![entry.clone(), Entry { metadata: entry.metadata.clone(), ..Entry::default() }];
+
+        for format in [Format::Text, Format::Sdk, Format::Ss] {
+            let text = format.write(&entries[..1]).unwrap();
+            assert_eq!(Format::detect(None, &text).parse(&text).unwrap()[0].puzzle, entry.puzzle, "{format}");
+            assert!(format.write(&entries).is_err());
+        }
+        let text = Format::Sdk.write(&entries[..1]).unwrap();
+        assert_eq!(Format::Sdk.parse(&text).unwrap(), entries[..1]);
+
+        let text = Format::Sdm.write(&entries).unwrap();
+        let parsed = Format::Sdm.parse(&text).unwrap();
+        assert_eq!(
+            parsed.iter().map(|entry| &entry.puzzle).collect::<Vec<_>>(),
+            [&entries[0].puzzle, &entries[1].puzzle]
+        );
+
+        let text = Format::OpenSudoku.write(&entries).unwrap();
+        assert_eq!(Format::OpenSudoku.parse(&text).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_write_unsupported() {
+        let puzzle = Puzzle::from_str(&format!("{PUZZLE}\nvariant diagonal")).unwrap();
+        assert!(Format::Sdk.write(&[Entry::new(puzzle.clone())]).is_err());
+        assert!(Format::Text.write(&[Entry::new(puzzle)]).is_ok());
+        assert!(Format::Sdm.write(&[Entry::new(Puzzle::from_str("1.3.....2..4...1").unwrap())]).is_err());
+    }
+
+    /// A file in one of the formats with an edit somewhere in it, which often
+    /// leaves it almost but not quite valid.
+    fn damaged_file() -> impl Strategy<Value = String> {
+        let mut entry = Entry::new(Puzzle::from_str(PUZZLE).unwrap());
+        entry.metadata.title = Some("Easy".to_string());
+        let killer = Entry::new(
+            Puzzle::from_str("1...\n....\n....\n...1\nregions aaab abbb cccd cddd\ncage 3 r1c2 r1c3").unwrap(),
+        );
+        let files: Vec<_> =
+            Format::ALL.iter().map(|format| format.write(std::slice::from_ref(&entry)).unwrap()).collect();
+        let files = [files, vec![Format::Json.write(&[killer]).unwrap()]].concat();
+
+        (sample::select(files), any::<prop::sample::Index>(), any::<char>(), 0..3).prop_map(
+            |(file, index, char, edit)| {
+                let mut chars: Vec<_> = file.chars().collect();
+                let index = index.index(chars.len());
+                match edit {
+                    0 => chars[index] = char,
+                    1 => chars.insert(index, char),
+                    _ => drop(chars.remove(index)),
+                }
+                chars.into_iter().collect()
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_arbitrary(text in prop_oneof![damaged_file(), "\\PC{0,200}"]) {
+            // Anything may be rejected, but nothing may panic, even when written back out.
+            for format in Format::ALL.into_iter().chain([Format::detect(None, &text)]) {
+                if let Ok(entries) = format.parse(&text) {
+                    let _ = format.write(&entries);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! A JSON document for each puzzle, for programs which would rather not parse
+//! the other formats, e.g.
+//!
+//! ```json
+//! {
+//!   "grid": "0507030600070008000...",
+//!   "variants": ["diagonal"],
+//!   "metadata": { "title": "Easy", "author": "Anonymous" }
+//! }
+//! ```
+//!
+//! A file may hold either a single document or a list of them.
+
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::algorithms::{AlgorithmKind, Limit, Outcome};
+use crate::format::{Entry, Metadata};
+use crate::metrics::Metrics;
+use crate::puzzle::{Puzzle, Regions};
+use crate::solution::base::BaseSolution;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Document {
+    /// The digit in each square in reading order, as written by
+    /// [`Puzzle::serialize`].
+    pub grid: String,
+    /// Which squares were filled in at the start rather than since, see
+    /// [`Puzzle::initially_filled`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub givens: Option<Vec<bool>>,
+    /// A label for each square naming the region it's in, for Jigsaw Sudoku.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regions: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cages: Vec<CageDocument>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CageDocument {
+    pub sum: u32,
+    /// The names of the squares in the cage, like `r1c1`.
+    pub squares: Vec<String>,
+}
+
+impl Document {
+    pub fn new(entry: &Entry) -> Self {
+        let puzzle = &entry.puzzle;
+        let dimensions = puzzle.dimensions;
+        let is_jigsaw = puzzle.regions != Regions::boxes(dimensions);
+        let cages = puzzle.cages.iter().map(|cage| CageDocument {
+            sum: cage.sum,
+            squares: cage.squares.iter().map(|&square| dimensions.square_name(square)).collect(),
+        });
+        Self {
+            grid: puzzle.serialize(),
+            givens: puzzle.initially_filled.clone(),
+            regions: is_jigsaw.then(|| puzzle.regions.serialize()),
+            cages: cages.collect(),
+            variants: puzzle.variants.iter().map(ToString::to_string).collect(),
+            metadata: entry.metadata.clone(),
+        }
+    }
+
+    pub fn into_entry(self) -> Result<Entry> {
+        let mut puzzle: Puzzle = self.grid.parse()?;
+        if let Some(givens) = self.givens {
+            if givens.len() != puzzle.data.len() {
+                return Err(anyhow!("givens has {} squares, but the grid has {}", givens.len(), puzzle.data.len()));
+            }
+            if let Some(index) = (0..givens.len()).find(|&index| givens[index] && puzzle.get(index).is_none()) {
+                return Err(anyhow!("square {} is given, but empty", puzzle.dimensions.square_name(index)));
+            }
+            puzzle.initially_filled = Some(givens);
+        }
+
+        // The rules are checked the same way as when they follow a grid in a text file.
+        let regions = self.regions.map(|regions| format!("regions {regions}"));
+        let cages = self.cages.into_iter().map(|cage| format!("cage {} {}", cage.sum, cage.squares.join(" ")));
+        let variants = self.variants.into_iter().map(|variant| format!("variant {variant}"));
+        for rule in regions.into_iter().chain(cages).chain(variants) {
+            puzzle.parse_rule(&rule)?;
+        }
+
+        Ok(Entry { puzzle, metadata: self.metadata })
+    }
+}
+
+/// A file holds either a single document or a list of them.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum Documents {
+    One(Box<Document>),
+    Many(Vec<Document>),
+}
+
+pub fn parse(text: &str) -> Result<Vec<Entry>> {
+    let documents = match serde_json::from_str(text)? {
+        Documents::One(document) => vec![*document],
+        Documents::Many(documents) => documents,
+    };
+    documents.into_iter().map(Document::into_entry).collect()
+}
+
+pub fn write(entries: &[Entry]) -> Result<String> {
+    let documents = match entries {
+        [entry] => Documents::One(Box::new(Document::new(entry))),
+        entries => Documents::Many(entries.iter().map(Document::new).collect()),
+    };
+    Ok(serde_json::to_string_pretty(&documents)? + "\n")
+}
+
+/// A puzzle along with its solution and how it was found.
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    #[serde(flatten)]
+    pub puzzle: Document,
+    /// The solved grid, in the same form as the puzzle's. If the solver gave
+    /// up, this is as far as it got.
+    pub solution: String,
+    pub algorithm: String,
+    /// How long the solver took, in milliseconds.
+    pub time_ms: f64,
+    /// The limit which the solver reached before finding the solution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gave_up: Option<Limit>,
+    /// How much work the solver did.
+    pub metrics: MetricsReport,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MetricsReport {
+    /// How many edits the solver made, which is what `--max-steps` limits.
+    pub square_edits: u64,
+    pub square_views: u64,
+}
+
+impl MetricsReport {
+    fn new(metrics: &Metrics) -> Self {
+        Self { square_edits: metrics.total_edits(), square_views: metrics.total_views() }
+    }
+}
+
+impl Report {
+    /// A report on solving `entry`, which `solution` was solving when the
+    /// algorithm returned `outcome`.
+    pub fn new(
+        entry: &Entry,
+        solution: &BaseSolution,
+        outcome: Outcome,
+        algorithm: AlgorithmKind,
+        time: Duration,
+    ) -> Self {
+        Self {
+            puzzle: Document::new(entry),
+            solution: solution.puzzle().serialize(),
+            algorithm: algorithm.to_string(),
+            time_ms: time.as_secs_f64() * 1000.0,
+            gave_up: match outcome {
+                Outcome::GaveUp(limit) => Some(limit),
+                Outcome::Solved | Outcome::Cancelled => None,
+            },
+            metrics: MetricsReport::new(&solution.metrics),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut puzzle =
+            Puzzle::from_str("1...............\nregions aaab abbb cccd cddd\ncage 3 r1c1 r1c2\nvariant diagonal")
+                .unwrap();
+        puzzle.track_initial();
+        let mut entry = Entry::new(puzzle);
+        entry.metadata.title = Some("Tiny".to_string());
+
+        let text = write(std::slice::from_ref(&entry)).unwrap();
+        assert!(text.contains("\"squares\": [\n        \"r1c1\",\n        \"r1c2\"\n      ]"));
+        assert_eq!(parse(&text).unwrap(), [entry.clone()]);
+
+        let text = write(&[entry.clone(), entry.clone()]).unwrap();
+        assert_eq!(parse(&text).unwrap(), [entry.clone(), entry]);
+    }
+
+    #[test]
+    fn test_parse() {
+        let entries = parse(r#"{"grid": "1.3.....2..4...1", "variants": ["anti-king"]}"#).unwrap();
+        assert_eq!(entries[0].puzzle.variants, [crate::puzzle::Variant::AntiKing]);
+        assert_eq!(entries[0].puzzle.get(2), Some(3));
+
+        assert!(parse(r#"{"grid": "1.3.....2..4...1", "givens": [true]}"#).is_err());
+        assert!(parse(r#"{"grid": "1.3.....2..4...1", "variants": ["sideways"]}"#).is_err());
+        assert!(parse(r#"{"variants": []}"#).is_err());
+    }
+}
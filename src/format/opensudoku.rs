@@ -0,0 +1,152 @@
+//! The XML format used by OpenSudoku to import collections of puzzles, e.g.
+//!
+//! ```xml
+//! <opensudoku>
+//!   <name>Easy</name>
+//!   <author>Anonymous</author>
+//!   <game data="050703060007000800000816000000030000005000100730040086906000204840572093000409000"/>
+//! </opensudoku>
+//! ```
+//!
+//! Its metadata is for the whole collection, so it's shared by each puzzle.
+//! Its backups instead put the games in `<folder>`s, which are named by an
+//! attribute, and are also read. Collections are written with folders when
+//! their puzzles have different titles.
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow};
+use roxmltree::{Document, Node};
+
+use crate::format::{Entry, Metadata};
+use crate::puzzle::Puzzle;
+
+pub fn parse(text: &str) -> Result<Vec<Entry>> {
+    let document = Document::parse(text)?;
+    let root = document.root_element();
+    if !root.has_tag_name("opensudoku") {
+        return Err(anyhow!("expected an <opensudoku> collection, found <{}>", root.tag_name().name()));
+    }
+
+    let text = |name: &str| {
+        let element = root.children().find(|node| node.has_tag_name(name))?;
+        let text = element.text()?.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    };
+    let metadata = Metadata {
+        title: text("name"),
+        author: text("author"),
+        difficulty: text("level"),
+        source: text("source"),
+        comment: text("description").or_else(|| text("comment")),
+    };
+
+    let games = root.descendants().filter(|node| node.has_tag_name("game"));
+    games
+        .enumerate()
+        .map(|(number, game)| {
+            let data = game.attribute("data").ok_or_else(|| anyhow!("missing data"));
+            let puzzle = data.and_then(Puzzle::from_str).with_context(|| format!("game {}", number + 1))?;
+            let mut metadata = metadata.clone();
+            if let Some(folder) = folder_name(game) {
+                metadata.title = Some(folder.to_string());
+            }
+            Ok(Entry { puzzle, metadata })
+        })
+        .collect()
+}
+
+/// The name of the folder a game was backed up from, if it was.
+fn folder_name<'a>(game: Node<'a, '_>) -> Option<&'a str> {
+    game.parent_element().filter(|parent| parent.has_tag_name("folder"))?.attribute("name")
+}
+
+/// Write the puzzles as a single collection.
+///
+/// If the puzzles have different titles, each run of them with the same title
+/// is put in a `<folder>` named after it, as in backups. The rest of the
+/// metadata can only be given for the whole collection, so it's an error for
+/// the puzzles to differ in any of it.
+pub fn write(entries: &[Entry]) -> Result<String> {
+    let shared = |name: &str, field: fn(&Metadata) -> &Option<String>| {
+        let first = entries.first().and_then(|entry| field(&entry.metadata).as_ref());
+        if entries.iter().any(|entry| field(&entry.metadata).as_ref() != first) {
+            return Err(anyhow!("OpenSudoku files only hold one {name} for the whole collection"));
+        }
+        Ok(first)
+    };
+    let title = shared("title", |metadata| &metadata.title).ok();
+    let elements = [
+        ("name", title.flatten()),
+        ("author", shared("author", |metadata| &metadata.author)?),
+        ("description", shared("comment", |metadata| &metadata.comment)?),
+        ("level", shared("difficulty", |metadata| &metadata.difficulty)?),
+        ("source", shared("source", |metadata| &metadata.source)?),
+    ];
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opensudoku>\n");
+    for (name, value) in elements {
+        if let Some(value) = value {
+            xml += &format!("  <{name}>{}</{name}>\n", escape(value));
+        }
+    }
+    let game = |entry: &Entry| format!("<game data=\"{}\"/>\n", entry.puzzle.serialize());
+    if title.is_some() {
+        xml.extend(entries.iter().map(|entry| format!("  {}", game(entry))));
+    } else {
+        for folder in entries.chunk_by(|a, b| a.metadata.title == b.metadata.title) {
+            let name = folder[0].metadata.title.as_ref();
+            let name = name.map(|name| format!(" name=\"{}\"", escape(name))).unwrap_or_default();
+            xml += &format!("  <folder{name}>\n");
+            xml.extend(folder.iter().map(|entry| format!("    {}", game(entry))));
+            xml += "  </folder>\n";
+        }
+    }
+    xml += "</opensudoku>\n";
+    Ok(xml)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_folders() {
+        let xml = r#"<opensudoku version="2">
+            <folder name="Easy &amp; short" created="0">
+                <game created="0" state="1" time="0" data="050703060007000800000816000000030000005000100730040086906000204840572093000409000"/>
+            </folder>
+        </opensudoku>"#;
+        let entries = parse(xml).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].metadata.title.as_deref(), Some("Easy & short"));
+        assert_eq!(entries[0].puzzle.get(1), Some(5));
+
+        assert!(parse("<sudoku/>").is_err());
+        assert!(parse("<opensudoku><game data=\"123\"/></opensudoku>").is_err());
+    }
+
+    #[test]
+    fn test_write_folders() {
+        let puzzle = Puzzle::default();
+        let entry = |title: Option<&str>| {
+            let mut entry = Entry::new(puzzle.clone());
+            entry.metadata.title = title.map(str::to_string);
+            entry.metadata.author = Some("Anonymous".to_string());
+            entry
+        };
+        let entries = [entry(Some("Easy")), entry(Some("Easy")), entry(None), entry(Some("Hard")), entry(Some("Easy"))];
+
+        let xml = write(&entries).unwrap();
+        assert_eq!(xml.matches("<folder").count(), 4);
+        assert_eq!(parse(&xml).unwrap(), entries);
+
+        let mut other = entry(None);
+        other.metadata.author = Some("Someone else".to_string());
+        assert!(write(&[entry(None), other]).is_err());
+    }
+}
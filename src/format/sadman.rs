@@ -0,0 +1,129 @@
+//! The formats used by SadMan Software's Sudoku programs.
+//!
+//! An `.sdk` file holds a single puzzle as a line per row, preceded by
+//! metadata lines made of `#`, a letter saying what the line holds, then its
+//! value, e.g. `#AAnonymous` for the author. It may also hold the state of a
+//! game in progress in a `[State]` section, which is ignored.
+//!
+//! An `.sdm` file holds a collection of puzzles, one per line.
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+
+use crate::format::{Entry, Metadata};
+use crate::puzzle::Puzzle;
+
+/// Returns true if the line is only found in `.sdk` files: a metadata line or
+/// the header of a section.
+pub(crate) fn is_sdk_line(line: &str) -> bool {
+    let mut chars = line.chars();
+    match (chars.next(), chars.next()) {
+        (Some('#'), Some(code)) => "ABCDLSU".contains(code) && chars.next().is_some_and(|char| !char.is_lowercase()),
+        (Some('['), _) => line.ends_with(']'),
+        _ => false,
+    }
+}
+
+/// Returns true if the line could hold a whole puzzle in an `.sdm` file.
+pub(crate) fn is_sdm_line(line: &str) -> bool {
+    line.len() == 81 && line.chars().all(|char| char.is_ascii_digit() || char == '.')
+}
+
+pub fn parse_sdk(text: &str) -> Result<Entry> {
+    let mut metadata = Metadata::default();
+    let mut grid = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            // The puzzle comes first, so anything after it is the state of a game.
+            if line != "[Puzzle]" {
+                break;
+            }
+        } else if let Some(field) = line.strip_prefix('#') {
+            let mut chars = field.chars();
+            let field = match chars.next() {
+                Some('D') => &mut metadata.title,
+                Some('A') => &mut metadata.author,
+                Some('L') => &mut metadata.difficulty,
+                Some('S') => &mut metadata.source,
+                Some('C') => &mut metadata.comment,
+                _ => continue,
+            };
+            let value = chars.as_str().trim();
+            if !value.is_empty() {
+                *field = Some(value.to_string());
+            }
+        } else {
+            grid.push(line);
+        }
+    }
+
+    let puzzle = Puzzle::from_str(&grid.join("\n"))?;
+    Ok(Entry { puzzle, metadata })
+}
+
+pub fn write_sdk(entry: &Entry) -> String {
+    let Metadata { title, author, difficulty, source, comment } = &entry.metadata;
+    let fields = [('D', title), ('A', author), ('L', difficulty), ('S', source), ('C', comment)];
+    let mut text = String::new();
+    for (code, value) in fields {
+        if let Some(value) = value {
+            // A line break would end the metadata line early, and what comes after it would be
+            // read as part of the grid.
+            let value: Vec<_> = value.split(['\r', '\n']).map(str::trim).filter(|line| !line.is_empty()).collect();
+            text += &format!("#{code}{}\n", value.join(" "));
+        }
+    }
+
+    let size = entry.puzzle.dimensions.size();
+    let grid: Vec<_> = entry.puzzle.serialize().replace('0', ".").chars().collect();
+    for row in grid.chunks(size) {
+        text.extend(row);
+        text.push('\n');
+    }
+    text
+}
+
+pub fn parse_sdm(text: &str) -> Result<Vec<Entry>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let puzzle = Puzzle::from_str(line).with_context(|| format!("line {}", number + 1))?;
+            Ok(Entry::new(puzzle))
+        })
+        .collect()
+}
+
+pub fn write_sdm(entries: &[Entry]) -> String {
+    entries.iter().map(|entry| entry.puzzle.serialize() + "\n").collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_sdk() {
+        let text = "#AAnonymous\n#DEasy one\n#BJan 1\n[Puzzle]\n.5.7.3.6.\n..7...8..\n...816...\n....3....\n..5...1..\n\
+            73..4..86\n9.6...2.4\n84.572.93\n...4.9...\n[State]\n15.7.3.6.\n";
+        let entry = parse_sdk(text).unwrap();
+        assert_eq!(entry.metadata.author.as_deref(), Some("Anonymous"));
+        assert_eq!(entry.metadata.title.as_deref(), Some("Easy one"));
+        assert_eq!(entry.puzzle.get(0), None);
+        assert_eq!(entry.puzzle.get(1), Some(5));
+        assert!(is_sdk_line("#AAnonymous"));
+        assert!(!is_sdk_line("# A comment"));
+        assert!(!is_sdk_line("#Diagonal puzzle"));
+    }
+
+    #[test]
+    fn test_write_sdk_line_breaks() {
+        let mut entry = Entry::new(Puzzle::default());
+        entry.metadata.title = Some("Two\r\nlines\r123456789".to_string());
+        let parsed = parse_sdk(&write_sdk(&entry)).unwrap();
+        assert_eq!(parsed.metadata.title.as_deref(), Some("Two lines 123456789"));
+        assert_eq!(parsed.puzzle, entry.puzzle);
+    }
+}
@@ -0,0 +1,42 @@
+//! The format used by Simple Sudoku, which splits the grid into boxes with
+//! `|` and lines of `-`, e.g. `.5.|7.3|.6.`. [`Puzzle::from_str`] reads it.
+
+use crate::puzzle::{Dimensions, Puzzle};
+
+pub fn write(puzzle: &Puzzle) -> String {
+    let Dimensions { box_rows, box_cols } = puzzle.dimensions;
+    let size = puzzle.dimensions.size();
+    let grid: Vec<_> = puzzle.serialize().replace('0', ".").chars().collect();
+    // One character per square, plus a bar between each box.
+    let separator = "-".repeat(size + size / box_cols - 1);
+
+    let mut text = String::new();
+    for (row, squares) in grid.chunks(size).enumerate() {
+        if row > 0 && row.is_multiple_of(box_rows) {
+            text += &separator;
+            text.push('\n');
+        }
+        for (col, char) in squares.iter().enumerate() {
+            if col > 0 && col.is_multiple_of(box_cols) {
+                text.push('|');
+            }
+            text.push(*char);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let puzzle = Puzzle::from_str("1.3.....2..4...1").unwrap();
+        assert_eq!(write(&puzzle), "1.|3.\n..|..\n-----\n2.|.4\n..|.1\n");
+        assert_eq!(Puzzle::from_str(&write(&puzzle)).unwrap(), puzzle);
+    }
+}
@@ -1,12 +1,10 @@
 use std::process;
-use std::sync::mpsc::{self, TryRecvError};
 
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 
-use crate::puzzle::Puzzle;
-use crate::tui::{KeyHandler, Movement, Tui};
-use crate::util::DivRem;
+use sudoku::tui::{KeyHandler, Movement, Tui};
+use sudoku::{CancellationToken, Dimensions, Puzzle};
 
 #[derive(Clone, Default)]
 enum GameKeys {
@@ -19,20 +17,22 @@ enum GameKeys {
 }
 
 impl KeyHandler for GameKeys {
+    type State = Puzzle;
+
     fn handle_key(self, tui: &mut Tui<Self>, puzzle: &mut Puzzle, key: KeyEvent) -> Self {
         match self {
             Self::Default => match key.code {
                 KeyCode::Char('h') => {
-                    tui.move_cursor(Movement::Left);
+                    tui.move_cursor(puzzle.dimensions, Movement::Left);
                 }
                 KeyCode::Char('j') => {
-                    tui.move_cursor(Movement::Down);
+                    tui.move_cursor(puzzle.dimensions, Movement::Down);
                 }
                 KeyCode::Char('k') => {
-                    tui.move_cursor(Movement::Up);
+                    tui.move_cursor(puzzle.dimensions, Movement::Up);
                 }
                 KeyCode::Char('l') => {
-                    tui.move_cursor(Movement::Right);
+                    tui.move_cursor(puzzle.dimensions, Movement::Right);
                 }
                 KeyCode::Backspace => {
                     puzzle.set(tui.cursor_square_index.unwrap(), None);
@@ -40,15 +40,15 @@ impl KeyHandler for GameKeys {
                 KeyCode::Char(',') => {
                     let index = tui.cursor_square_index.unwrap();
                     if let Some(prev_index) = puzzle.prev_empty(index) {
-                        let (row, column) = prev_index.div_rem(9);
-                        tui.move_cursor(Movement::To { row, column });
+                        let (row, column) = puzzle.dimensions.position(prev_index);
+                        tui.move_cursor(puzzle.dimensions, Movement::To { row, column });
                     }
                 }
                 KeyCode::Char('.') => {
                     let index = tui.cursor_square_index.unwrap();
                     if let Some(next_index) = puzzle.next_empty(index) {
-                        let (row, column) = next_index.div_rem(9);
-                        tui.move_cursor(Movement::To { row, column });
+                        let (row, column) = puzzle.dimensions.position(next_index);
+                        tui.move_cursor(puzzle.dimensions, Movement::To { row, column });
                     }
                 }
                 KeyCode::Char(' ') => {
@@ -61,10 +61,12 @@ impl KeyHandler for GameKeys {
                 KeyCode::Char('g') => {
                     return Self::GoRow;
                 }
+                KeyCode::Char('p') => {
+                    tui.highlight_peers = !tui.highlight_peers;
+                }
                 KeyCode::Char(char) => {
-                    if let Some(digit) = char.to_digit(10) {
-                        // This cast to u8 is safe since digit will only ever be 0-9.
-                        puzzle.set(tui.cursor_square_index.unwrap(), Some(digit as u8));
+                    if let Some(digit) = entered_digit(char, puzzle.dimensions) {
+                        puzzle.set(tui.cursor_square_index.unwrap(), Some(digit));
 
                         if puzzle.is_filled_out() {
                             match puzzle.validate() {
@@ -84,8 +86,7 @@ impl KeyHandler for GameKeys {
                     return Self::Default;
                 }
                 KeyCode::Char(char) => {
-                    if let Some(digit) = char.to_digit(10) {
-                        // This cast to usize is safe since digit will only ever be 0-9.
+                    if let Some(digit) = entered_digit(char, puzzle.dimensions) {
                         return Self::GoColumn { row: digit as usize };
                     }
                 }
@@ -96,13 +97,10 @@ impl KeyHandler for GameKeys {
                     return Self::Default;
                 }
                 KeyCode::Char(char) => {
-                    if let Some(digit) = char.to_digit(10) {
-                        // We 1-index the g-<row>-<column> command, so g-0-<column> and g-<row>-0 are
-                        // non-sensical.
-                        if row > 0 && digit > 0 {
-                            // This usize cast and subtracting 1 is safe since digit will only ever be 1-9.
-                            tui.move_cursor(Movement::To { row: row - 1, column: digit as usize - 1 });
-                        }
+                    if let Some(digit) = entered_digit(char, puzzle.dimensions) {
+                        // We 1-index the g-<row>-<column> command, and entered digits are never 0, so
+                        // subtracting 1 is safe.
+                        tui.move_cursor(puzzle.dimensions, Movement::To { row: row - 1, column: digit as usize - 1 });
                         return Self::Default;
                     }
                 }
@@ -113,11 +111,21 @@ impl KeyHandler for GameKeys {
     }
 }
 
+/// The digit a key press enters into a puzzle of the given shape, if any.
+///
+/// Digits past 9 are entered as uppercase letters, since lowercase letters are
+/// taken by commands.
+fn entered_digit(char: char, dimensions: Dimensions) -> Option<u8> {
+    if char.is_ascii_lowercase() {
+        return None;
+    }
+    dimensions.parse_digit(char)
+}
+
 pub fn play(mut puzzle: Puzzle) -> Result<()> {
     puzzle.track_initial();
-    let (tx, rx) = mpsc::sync_channel(1);
-    let mut tui = Tui::<GameKeys>::init(tx).with_cursor();
-    while let Err(TryRecvError::Empty) = rx.try_recv() {
+    let mut tui = Tui::<GameKeys>::init(CancellationToken::new()).with_cursor().with_peer_highlighting();
+    while !tui.is_killed() {
         tui.render(&mut puzzle)?;
     }
     Ok(())
@@ -0,0 +1,333 @@
+//! Where the lines and squares of a grid drawn with box-drawing characters go,
+//! shared by the TUI and [`render`](crate::render).
+
+use crate::puzzle::Puzzle;
+
+/// A position within a grid, in characters from its top-left corner.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Position {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// Where everything in a grid is drawn, relative to its top-left corner.
+///
+/// The grid has thick lines around regions (usually the boxes) and, unless it
+/// is compact, thin lines between the squares within them. Each square is a
+/// row of `cell_width` characters, with its value in the middle.
+pub struct Layout {
+    cells: Vec<Vec<Cell>>,
+    /// The position of each square's value, indexed by square.
+    squares: Vec<Position>,
+    cell_width: u16,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Cell {
+    /// Render this glyph.
+    Glyph(char),
+    /// Render whitespace.
+    Space,
+    /// Render the value of the square at this index.
+    Square(usize),
+}
+
+/// A single line of characters across or down the grid.
+#[derive(Copy, Clone)]
+enum Track {
+    /// The border before the `n`th row or column of squares, or after the last
+    /// one.
+    Line { n: usize },
+    /// Part of the `n`th row or column of squares, `offset` characters in.
+    Square { n: usize, offset: usize },
+}
+
+/// How heavily a line is drawn.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Weight {
+    None,
+    /// Between squares in the same region.
+    Light,
+    /// Between regions, and around the edge of the grid.
+    Heavy,
+}
+
+impl Layout {
+    /// Lay out `puzzle` with squares `cell_width` characters wide.
+    pub fn new(puzzle: &Puzzle, cell_width: usize) -> Self {
+        Self::build(puzzle, cell_width, false)
+    }
+
+    /// Lay out `puzzle` as small as possible, with squares a single character
+    /// wide and lines only where they are needed to separate regions.
+    pub fn compact(puzzle: &Puzzle) -> Self {
+        Self::build(puzzle, 1, true)
+    }
+
+    fn build(puzzle: &Puzzle, cell_width: usize, compact: bool) -> Self {
+        assert!(cell_width > 0, "squares must be at least one character wide");
+        let dimensions = puzzle.dimensions;
+        let size = dimensions.size();
+        let region = |row: usize, col: usize| puzzle.regions.id(dimensions.index(row, col));
+        let weight = |divides: bool| if divides { Weight::Heavy } else { Weight::Light };
+        // The weight of the line above `row`, in column `col`.
+        let across =
+            |row: usize, col: usize| weight(row == 0 || row == size || region(row - 1, col) != region(row, col));
+        // The weight of the line left of `col`, in row `row`.
+        let down = |row: usize, col: usize| weight(col == 0 || col == size || region(row, col - 1) != region(row, col));
+
+        let row_lines: Vec<_> =
+            (0..=size).map(|row| !compact || (0..size).any(|col| across(row, col) == Weight::Heavy)).collect();
+        let column_lines: Vec<_> =
+            (0..=size).map(|col| !compact || (0..size).any(|row| down(row, col) == Weight::Heavy)).collect();
+        let rows = tracks(&row_lines, 1);
+        let columns = tracks(&column_lines, cell_width);
+
+        let mut cells = Vec::with_capacity(rows.len());
+        let mut squares = vec![Position::default(); dimensions.squares()];
+        for (y, &row_track) in rows.iter().enumerate() {
+            let mut line = Vec::with_capacity(columns.len());
+            for (x, &column_track) in columns.iter().enumerate() {
+                let cell = match (row_track, column_track) {
+                    (Track::Line { n: row }, Track::Line { n: col }) => {
+                        let up = if row > 0 { down(row - 1, col) } else { Weight::None };
+                        let right = if col < size { across(row, col) } else { Weight::None };
+                        let below = if row < size { down(row, col) } else { Weight::None };
+                        let left = if col > 0 { across(row, col - 1) } else { Weight::None };
+                        Cell::Glyph(glyph(up, right, below, left))
+                    }
+                    (Track::Line { n: row }, Track::Square { n: col, .. }) => {
+                        let weight = across(row, col);
+                        Cell::Glyph(glyph(Weight::None, weight, Weight::None, weight))
+                    }
+                    (Track::Square { n: row, .. }, Track::Line { n: col }) => {
+                        let weight = down(row, col);
+                        Cell::Glyph(glyph(weight, Weight::None, weight, Weight::None))
+                    }
+                    (Track::Square { n: row, .. }, Track::Square { n: column, offset }) => {
+                        if offset == cell_width / 2 {
+                            let index = dimensions.index(row, column);
+                            // These casts are safe since the layout of even the largest puzzle
+                            // will never overflow u16.
+                            squares[index] = Position { x: x as u16, y: y as u16 };
+                            Cell::Square(index)
+                        } else {
+                            Cell::Space
+                        }
+                    }
+                };
+                line.push(cell);
+            }
+            cells.push(line);
+        }
+
+        Self { cells, squares, cell_width: cell_width as u16 }
+    }
+
+    /// The cells of the grid, row by row.
+    pub fn rows(&self) -> &[Vec<Cell>] {
+        &self.cells
+    }
+
+    pub fn width(&self) -> u16 {
+        // This cast is safe since the layout of even the largest puzzle will never
+        // overflow u16.
+        self.cells[0].len() as u16
+    }
+
+    pub fn height(&self) -> u16 {
+        self.cells.len() as u16
+    }
+
+    /// Where the value of the square at `index` is drawn.
+    pub fn square_position(&self, index: usize) -> Position {
+        self.squares[index]
+    }
+
+    /// Where the first character of the square at `index` is drawn.
+    pub fn square_start(&self, index: usize) -> Position {
+        let position = self.squares[index];
+        Position { x: position.x - self.cell_width / 2, ..position }
+    }
+
+    /// How many characters wide each square is.
+    pub fn cell_width(&self) -> u16 {
+        self.cell_width
+    }
+
+    /// What is drawn at `position`, if it is within the grid.
+    pub fn cell(&self, position: Position) -> Option<Cell> {
+        self.cells.get(position.y as usize)?.get(position.x as usize).copied()
+    }
+
+    /// The square drawn at `position`, if any.
+    ///
+    /// The whole width of a square counts as part of it, not just the
+    /// character its value is drawn in.
+    #[cfg(test)]
+    pub fn square_at(&self, position: Position) -> Option<usize> {
+        (0..self.squares.len()).find(|&index| {
+            let start = self.square_start(index);
+            start.y == position.y && (start.x..start.x + self.cell_width).contains(&position.x)
+        })
+    }
+}
+
+/// Lay out the tracks across (or down) the grid, with each square `cell_len`
+/// characters long and a line wherever `lines` says there is one.
+fn tracks(lines: &[bool], cell_len: usize) -> Vec<Track> {
+    let mut tracks = Vec::new();
+    for (n, &line) in lines.iter().enumerate() {
+        if line {
+            tracks.push(Track::Line { n });
+        }
+        // There is one more line than there are squares.
+        if n + 1 < lines.len() {
+            tracks.extend((0..cell_len).map(|offset| Track::Square { n, offset }));
+        }
+    }
+    tracks
+}
+
+/// Box drawing glyphs, indexed by the weights of their arms going up, right,
+/// down and left, as the digits of a base 3 number.
+#[rustfmt::skip]
+const GLYPHS: [char; 81] = [
+    ' ', '╴', '╸', '╷', '┐', '┑', '╻', '┒', '┓',
+    '╶', '─', '╾', '┌', '┬', '┭', '┎', '┰', '┱',
+    '╺', '╼', '━', '┍', '┮', '┯', '┏', '┲', '┳',
+    '╵', '┘', '┙', '│', '┤', '┥', '╽', '┧', '┪',
+    '└', '┴', '┵', '├', '┼', '┽', '┟', '╁', '╅',
+    '┕', '┶', '┷', '┝', '┾', '┿', '┢', '╆', '╈',
+    '╹', '┚', '┛', '╿', '┦', '┩', '┃', '┨', '┫',
+    '┖', '┸', '┹', '┞', '╀', '╃', '┠', '╂', '╉',
+    '┗', '┺', '┻', '┡', '╄', '╇', '┣', '╊', '╋',
+];
+
+/// The glyph with arms of the given weights.
+fn glyph(up: Weight, right: Weight, down: Weight, left: Weight) -> char {
+    let digit = |weight: Weight| weight as usize;
+    GLYPHS[digit(up) * 27 + digit(right) * 9 + digit(down) * 3 + digit(left)]
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::puzzle::Dimensions;
+
+    const SMALL: Dimensions = Dimensions { box_rows: 2, box_cols: 2 };
+
+    /// Render the layout as text, with squares as their (hex) index.
+    fn render(layout: &Layout) -> Vec<String> {
+        layout
+            .rows()
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|cell| match cell {
+                        Cell::Glyph(glyph) => *glyph,
+                        Cell::Space => ' ',
+                        Cell::Square(index) => char::from_digit(*index as u32, 16).unwrap(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(
+            render(&Layout::new(&Puzzle::empty(SMALL), 3)),
+            [
+                "┏━━━┯━━━┳━━━┯━━━┓",
+                "┃ 0 │ 1 ┃ 2 │ 3 ┃",
+                "┠───┼───╂───┼───┨",
+                "┃ 4 │ 5 ┃ 6 │ 7 ┃",
+                "┣━━━┿━━━╋━━━┿━━━┫",
+                "┃ 8 │ 9 ┃ a │ b ┃",
+                "┠───┼───╂───┼───┨",
+                "┃ c │ d ┃ e │ f ┃",
+                "┗━━━┷━━━┻━━━┷━━━┛",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compact_layout() {
+        assert_eq!(
+            render(&Layout::compact(&Puzzle::empty(SMALL))),
+            ["┏━━┳━━┓", "┃01┃23┃", "┃45┃67┃", "┣━━╋━━┫", "┃89┃ab┃", "┃cd┃ef┃", "┗━━┻━━┛"]
+        );
+    }
+
+    #[test]
+    fn test_jigsaw_layout() {
+        let puzzle = Puzzle::from_str("................\nregions aaab abbb cccd cddd").unwrap();
+        let layout = render(&Layout::new(&puzzle, 1));
+        assert_eq!(
+            layout,
+            [
+                "┏━┯━┯━┳━┓",
+                "┃0│1│2┃3┃",
+                "┠─╆━┿━╃─┨",
+                "┃4┃5│6│7┃",
+                "┣━╇━┿━╈━┫",
+                "┃8│9│a┃b┃",
+                "┠─╆━┿━╃─┨",
+                "┃c┃d│e│f┃",
+                "┗━┻━┷━┷━┛",
+            ]
+        );
+        // The line between the second and third columns never divides two regions.
+        assert_eq!(
+            render(&Layout::compact(&puzzle)),
+            [
+                "┏━┯━━┳━┓",
+                "┃0│12┃3┃",
+                "┠─╆━━╃─┨",
+                "┃4┃56│7┃",
+                "┣━╇━━╈━┫",
+                "┃8│9a┃b┃",
+                "┠─╆━━╃─┨",
+                "┃c┃de│f┃",
+                "┗━┻━━┷━┛"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_standard_layout_size() {
+        // The size of the grid before it was generated.
+        let layout = Layout::new(&Puzzle::default(), 3);
+        assert_eq!((layout.width(), layout.height()), (37, 19));
+        assert_eq!(Layout::new(&Puzzle::default(), 5).width(), 55);
+        assert_eq!(
+            (Layout::compact(&Puzzle::default()).width(), Layout::compact(&Puzzle::default()).height()),
+            (13, 13)
+        );
+    }
+
+    #[test]
+    fn test_square_positions() {
+        let layout = Layout::new(&Puzzle::empty(SMALL), 3);
+        assert_eq!(layout.square_position(0), Position { x: 2, y: 1 });
+        assert_eq!(layout.square_position(6), Position { x: 10, y: 3 });
+        assert_eq!(layout.square_start(6), Position { x: 9, y: 3 });
+
+        for index in 0..SMALL.squares() {
+            assert_eq!(layout.square_at(layout.square_position(index)), Some(index));
+        }
+        // Either side of a square's value is still part of it, but lines aren't.
+        assert_eq!(layout.square_at(Position { x: 9, y: 3 }), Some(6));
+        assert_eq!(layout.square_at(Position { x: 11, y: 3 }), Some(6));
+        assert_eq!(layout.square_at(Position { x: 8, y: 3 }), None);
+        assert_eq!(layout.square_at(Position { x: 10, y: 2 }), None);
+        assert_eq!(layout.square_at(Position { x: 10, y: 9 }), None);
+
+        let layout = Layout::compact(&Puzzle::empty(SMALL));
+        assert_eq!(layout.square_at(Position { x: 2, y: 2 }), Some(5));
+        assert_eq!(layout.square_at(Position { x: 4, y: 4 }), Some(10));
+    }
+}
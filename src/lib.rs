@@ -0,0 +1,39 @@
+//! Solve and play Sudoku puzzles.
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use sudoku::{Algorithm, BaseSolution, ConstraintPropagation, Puzzle};
+//!
+//! let puzzle =
+//!     Puzzle::from_str("050703060007000800000816000000030000005000100730040086906000204840572093000409000")?;
+//! let mut solution = BaseSolution::new(puzzle);
+//! ConstraintPropagation.solve(&mut solution, None)?;
+//!
+//! assert!(solution.puzzle().is_filled_out());
+//! assert!(solution.puzzle().validate().is_ok());
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The TUI and the pieces the command line program is built from are only
+//! available with the `cli` feature, which is enabled by default.
+
+pub mod algorithms;
+pub mod cancel;
+pub mod export;
+pub mod format;
+pub mod layout;
+pub mod metrics;
+pub mod puzzle;
+pub mod render;
+pub mod solution;
+pub mod trace;
+#[cfg(feature = "cli")]
+pub mod tui;
+pub(crate) mod util;
+
+pub use algorithms::{Algorithm, AlgorithmKind, Backtracking, ConstraintPropagation, Limits, Outcome};
+pub use cancel::CancellationToken;
+pub use puzzle::{Dimensions, Puzzle};
+pub use solution::Solution;
+pub use solution::base::BaseSolution;
@@ -1,36 +1,45 @@
 use std::borrow::Cow;
-use std::path::PathBuf;
-use std::str::FromStr;
-use std::sync::mpsc;
-use std::{fs, process};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{env, fs, process, thread};
 
+use anyhow::{Result, anyhow};
 use clap::{Parser, ValueEnum};
 
-use crate::algorithms::{Algorithm, Backtracking};
-use crate::puzzle::Puzzle;
-use crate::solution::base::BaseSolution;
-use crate::solution::tui::TuiSolution;
+use sudoku::algorithms::Limit;
+use sudoku::export::{self, Fill, Page};
+use sudoku::format::{Entry, Format, json};
+use sudoku::puzzle::Variant;
+use sudoku::render::{Renderer, Style};
+use sudoku::solution::progress::Progress;
+use sudoku::solution::trace::TraceSolution;
+use sudoku::solution::tui::TuiSolution;
+use sudoku::trace::{self, SharedTrace};
+use sudoku::tui::animation::{Animation, Lane};
+use sudoku::{Algorithm, AlgorithmKind, BaseSolution, CancellationToken, Limits, Outcome, Puzzle, Solution};
 
-mod algorithms;
 mod game;
-#[cfg(debug_assertions)]
-mod metrics;
-mod puzzle;
-mod solution;
-mod tui;
-mod util;
-
-const PUZZLE_DIGITS: usize = 9_usize.pow(2);
 
 #[derive(Parser)]
 struct Cli {
     #[command(subcommand)]
     subcommand: Subcommand,
-    /// 81-digit string representing the puzzle, with unsolved squares as 0s
+    /// String of digits representing the puzzle, with unsolved squares as 0s. Its length
+    /// determines the size of the puzzle, e.g. 81 digits for a 9x9 puzzle or 256 for 16x16
     puzzle: Option<String>,
-    /// File containing puzzle data.
+    /// File containing puzzle data, either on one line or with a line per row.
     #[arg(short, long)]
     file: Option<PathBuf>,
+    /// The format of the puzzle, detected from the file's extension or its contents if not given
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    /// Which puzzle to use from a file holding several, counting from 1
+    #[arg(long, default_value_t = 1)]
+    number: usize,
+    /// Extra rules the puzzle follows, e.g. `--variant diagonal,anti-knight`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    variant: Vec<Variant>,
 }
 
 #[derive(clap::Subcommand)]
@@ -40,86 +49,373 @@ enum Subcommand {
         /// How to output the solution
         #[arg(value_enum, short, long, default_value_t)]
         output: Output,
+        /// The format to write the solution in for `--output=standard`, which is the puzzle's
+        /// format if not given
+        #[arg(value_enum, long)]
+        output_format: Option<Format>,
         /// The delay in milliseconds between edits for `--output=animation`
         #[arg(long)]
         animation_delay_ms: Option<u64>,
+        /// Write every edit made while solving to this file
+        #[arg(long, conflicts_with = "compare")]
+        trace: Option<PathBuf>,
+        /// The algorithm to solve with
+        #[arg(value_enum, short, long, default_value_t)]
+        algorithm: AlgorithmKind,
+        /// Animate several algorithms solving the puzzle side by side, e.g.
+        /// `--compare backtracking,propagation`
+        #[arg(long, value_enum, value_delimiter = ',', num_args = 1, conflicts_with = "algorithm")]
+        compare: Vec<AlgorithmKind>,
+        /// Give up if the puzzle isn't solved within this many milliseconds
+        #[arg(long, value_name = "MS")]
+        timeout: Option<u64>,
+        /// Give up if the puzzle isn't solved within this many edits. Animations
+        /// stop after a million edits unless this is given
+        #[arg(long, value_name = "N")]
+        max_steps: Option<u64>,
+    },
+    /// Animate a trace written by `solve --trace`
+    Replay {
+        /// The trace file to replay
+        trace: PathBuf,
+        /// The delay in milliseconds between edits
+        #[arg(long)]
+        animation_delay_ms: Option<u64>,
     },
     /// Play the given puzzle
     Play,
+    /// Render the given puzzle as a printable SVG image or PDF
+    Export {
+        /// Write an SVG image of the puzzle to this file
+        #[arg(long, required_unless_present = "pdf")]
+        svg: Option<PathBuf>,
+        /// Write a PDF of the puzzle to this file
+        #[arg(long)]
+        pdf: Option<PathBuf>,
+        /// Fill in the solution, in lighter digits than the givens
+        #[arg(long)]
+        solution: bool,
+        /// Pencil in the digits each empty square could hold
+        #[arg(long, conflicts_with = "solution")]
+        candidates: bool,
+        /// Lay out every puzzle in the file as a PDF booklet with this many to each A4 page, followed by
+        /// their solutions
+        #[arg(long, requires = "pdf", conflicts_with_all = ["svg", "solution", "candidates"])]
+        booklet: Option<usize>,
+        /// The algorithm to find solutions with
+        #[arg(value_enum, short, long, default_value_t)]
+        algorithm: AlgorithmKind,
+    },
 }
 
 #[derive(Clone, Default, ValueEnum)]
 enum Output {
-    /// Print the solution in standard puzzle notation
+    /// Print the solution on a single line in standard puzzle notation, or in `--output-format`
     #[default]
     Standard,
-    /// Pretty print the solution
+    /// Print a line for each row of the solution
+    Rows,
+    /// Pretty print the solution as a grid split into boxes
     Pretty,
+    /// Pretty print the solution with Unicode box-drawing characters
+    Unicode,
+    /// Print the solution as a Markdown table, with the givens in bold
+    Markdown,
+    /// Print the puzzle and the solution side by side, with the solved digits highlighted
+    Diff,
+    /// Print a line such as `r1c2=4` for each square that was solved
+    Changes,
     /// Render a TUI that shows the solution being found
     Animation,
 }
 
-fn main() {
-    const ALGORITHM: Backtracking = Backtracking;
+impl Output {
+    /// How to print the solution as text, unless it is animated.
+    fn style(&self) -> Option<Style> {
+        match self {
+            Self::Standard => Some(Style::Line),
+            Self::Rows => Some(Style::Rows),
+            Self::Pretty => Some(Style::Ascii),
+            Self::Unicode => Some(Style::Unicode),
+            Self::Markdown => Some(Style::Markdown),
+            Self::Diff => Some(Style::Diff),
+            Self::Changes => Some(Style::Changes),
+            Self::Animation => None,
+        }
+    }
+}
 
+fn main() {
     env_logger::init();
     let cli = Cli::parse();
-    let puzzle_text = match (cli.puzzle, cli.file) {
+    let puzzle_text = match (cli.puzzle, &cli.file) {
         (Some(puzzle), None) => Some(puzzle),
-        (None, Some(file)) => Some(fs::read_to_string(file).unwrap()),
+        (None, Some(file)) => Some(fs::read_to_string(file).unwrap_or_else(|error| exit(format!("{error}")))),
         (None, None) => None,
         _ => {
             exit("only one of [PUZZLE] and -f <FILE> may be provided");
         }
     };
 
-    let puzzle = match puzzle_text {
-        Some(text) => Puzzle::from_str(text.trim()).unwrap(),
-        None => Puzzle::default(),
+    let has_puzzle = puzzle_text.is_some() || cli.format.is_some() || !cli.variant.is_empty();
+    let (format, mut entries) = match puzzle_text {
+        Some(text) => {
+            let format = cli.format.unwrap_or_else(|| Format::detect(cli.file.as_deref(), &text));
+            let entries = format.parse(&text).unwrap_or_else(|error| exit(format!("{error:#}")));
+            (format, entries)
+        }
+        None => (Format::Text, vec![Entry::default()]),
     };
+    for Entry { puzzle, .. } in &mut entries {
+        for &variant in &cli.variant {
+            if !puzzle.variants.contains(&variant) {
+                puzzle.variants.push(variant);
+            }
+        }
+    }
+    let Entry { puzzle, metadata } = select_entry(&entries, format, cli.number);
 
     match cli.subcommand {
-        Subcommand::Solve { output, animation_delay_ms } => {
+        Subcommand::Solve {
+            output,
+            output_format,
+            animation_delay_ms,
+            trace,
+            algorithm,
+            compare,
+            timeout,
+            max_steps,
+        } => {
             if animation_delay_ms.is_some() && !matches!(output, Output::Animation) {
                 exit("--animation-delay-ms can only be used in combination with --output=animation");
             }
+            if !compare.is_empty() && !matches!(output, Output::Animation) {
+                exit("--compare can only be used in combination with --output=animation");
+            }
+            if output_format.is_some() && !matches!(output, Output::Standard) {
+                exit("--output-format can only be used in combination with --output=standard");
+            }
+            let limits = Limits { timeout: timeout.map(Duration::from_millis), max_steps };
+            // The solvers wait for the animation to catch up with them, which would count
+            // towards the timeout.
+            if timeout.is_some() && matches!(output, Output::Animation) {
+                exit("--timeout can't be used in combination with --output=animation");
+            }
 
-            let (result, solution) = match output {
-                Output::Standard | Output::Pretty => {
-                    let mut solution = BaseSolution::new(puzzle);
-                    let result = ALGORITHM.solve(&mut solution, None);
-                    (result, solution)
-                }
+            let started = Instant::now();
+            let solution = match output {
                 Output::Animation => {
-                    let (tx, rx) = mpsc::sync_channel(1);
-                    let mut tui = TuiSolution::init(puzzle, tx, animation_delay_ms);
-                    let result = ALGORITHM.solve(&mut tui, Some(rx));
-                    (result, tui.into_base())
+                    let algorithms = if compare.is_empty() { vec![algorithm] } else { compare };
+                    animate(puzzle, &algorithms, trace, animation_delay_ms, max_steps)
+                }
+                _ => {
+                    let solution = BaseSolution::new(puzzle).with_limits(limits);
+                    // The other outputs are usually read by people, or by programs which have no use
+                    // for it.
+                    let show_progress = matches!(output, Output::Standard) && io::stderr().is_terminal();
+                    let solution =
+                        if show_progress { solution.with_observer(SPINNER_INTERVAL, spinner()) } else { solution };
+                    let result = solve(algorithm, solution, trace.as_deref(), None);
+                    if show_progress {
+                        eprint!("\r\x1b[2K");
+                    }
+                    result
                 }
             };
 
-            if let Err(error) = result {
-                exit(format!("{error}"));
-            }
+            let (solution, outcome) = match solution {
+                Ok(solution) => solution,
+                Err(error) => exit(format!("{error}")),
+            };
+            let elapsed = started.elapsed();
 
-            match output {
-                Output::Standard => {
-                    println!("{}", solution.puzzle);
+            // Givens are told apart from the solved digits by color, but only on a terminal.
+            let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+            let renderer = Renderer::new(solution.puzzle()).with_initial(&solution.initial).with_color(color);
+            match (output.style(), output_format.unwrap_or(format)) {
+                (None, _) => {}
+                (Some(Style::Line), Format::Json) => {
+                    let entry = Entry { puzzle: solution.initial.clone(), metadata };
+                    let report = json::Report::new(&entry, &solution, outcome, algorithm, elapsed);
+                    print!("{}", report.to_json().unwrap_or_else(|error| exit(format!("{error}"))));
                 }
-                Output::Pretty => {
-                    println!("{}", solution.puzzle.serialize());
+                // Only the JSON report has somewhere to say that the solution is unfinished.
+                _ if matches!(outcome, Outcome::GaveUp(_)) => {}
+                (Some(Style::Line), format) if format != Format::Text => {
+                    let entry = Entry { puzzle: solution.puzzle().clone(), metadata };
+                    let text = format.write(&[entry]).unwrap_or_else(|error| exit(format!("{error}")));
+                    print!("{text}");
                 }
-                Output::Animation => {}
+                (Some(Style::Line), _) => println!("{}", renderer.render(Style::Line)),
+                (Some(style), _) => print!("{}", renderer.render(style)),
             }
 
-            #[cfg(debug_assertions)]
             solution.metrics.write_logs();
+
+            // Animations only record so many edits unless told otherwise, which isn't the
+            // solver failing.
+            if matches!(output, Output::Animation) && max_steps.is_none() && outcome == Outcome::GaveUp(Limit::MaxSteps)
+            {
+                eprintln!("stopped recording after {} steps, which --max-steps can raise", solution.steps());
+                return;
+            }
+            if let Outcome::GaveUp(limit) = outcome {
+                eprintln!("gave up after {} steps, since the solver {limit}", solution.steps());
+                process::exit(2);
+            }
+        }
+        Subcommand::Replay { trace, animation_delay_ms } => {
+            if has_puzzle {
+                exit(
+                    "replay reads its puzzle from the trace file, so [PUZZLE], -f <FILE>, --format and --variant may not be given",
+                );
+            }
+
+            let text = fs::read_to_string(trace).unwrap_or_else(|error| exit(format!("{error}")));
+            let (puzzle, edits) = trace::parse(&text).unwrap_or_else(|error| exit(format!("{error:#}")));
+            let lanes = vec![Lane::new(None, puzzle, SharedTrace::finished(edits))];
+            if let Err(error) = Animation::init(lanes, CancellationToken::new(), animation_delay_ms).run() {
+                exit(format!("{error}"));
+            }
         }
         Subcommand::Play => {
             if let Err(error) = game::play(puzzle) {
                 exit(format!("{error}"));
             }
         }
+        Subcommand::Export { svg, pdf, solution, candidates, booklet, algorithm } => {
+            let solve_puzzle = |puzzle: &Puzzle| match solve(algorithm, BaseSolution::new(puzzle.clone()), None, None) {
+                Ok((solution, _)) => solution.into_puzzle(),
+                Err(error) => exit(format!("{error}")),
+            };
+            let write = |path: &Path, contents: &[u8]| {
+                if let Err(error) = fs::write(path, contents) {
+                    exit(format!("{}: {error}", path.display()));
+                }
+            };
+
+            if let Some(per_page) = booklet {
+                if per_page == 0 {
+                    exit("--booklet must be at least 1");
+                }
+                let solutions: Vec<_> = entries.iter().map(|entry| solve_puzzle(&entry.puzzle)).collect();
+                let pages = export::booklet(&entries, &solutions, per_page);
+                // --booklet requires --pdf, so this unwrap is safe.
+                write(&pdf.unwrap(), &export::pdf::write(&pages));
+                return;
+            }
+
+            let solved = solution.then(|| solve_puzzle(&puzzle));
+            let fill = match &solved {
+                Some(solution) => Fill::Solution(solution),
+                None if candidates => Fill::Candidates,
+                None => Fill::Empty,
+            };
+            let page = Page::single(&Entry { puzzle, metadata }, fill);
+            if let Some(path) = svg {
+                write(&path, export::svg::write(&page).as_bytes());
+            }
+            if let Some(path) = pdf {
+                write(&path, &export::pdf::write(&[page]));
+            }
+        }
+    }
+}
+
+/// The puzzle numbered `number`, counting from 1, out of those read from a
+/// file in `format`.
+fn select_entry(entries: &[Entry], format: Format, number: usize) -> Entry {
+    let count = entries.len();
+    match number.checked_sub(1).and_then(|index| entries.get(index)) {
+        Some(entry) => entry.clone(),
+        None => exit(format!("--number must be from 1 to {count}, the number of puzzles in the {format} file")),
+    }
+}
+
+/// Solve the puzzle held by `solution`, writing a trace of the edits made to
+/// `trace` if given.
+fn solve<S: Solution>(
+    algorithm: AlgorithmKind,
+    mut solution: S,
+    trace: Option<&Path>,
+    cancel: Option<&CancellationToken>,
+) -> Result<(S, Outcome)> {
+    match trace {
+        Some(path) => {
+            let mut solution = TraceSolution::create(solution, path)?;
+            let outcome = algorithm.solve(&mut solution, cancel)?;
+            Ok((solution.finish()?, outcome))
+        }
+        None => {
+            let outcome = algorithm.solve(&mut solution, cancel)?;
+            Ok((solution, outcome))
+        }
+    }
+}
+
+/// How many edits each solver may make beyond those the animation has shown,
+/// which is enough to jump ahead a fair way without waiting on the solvers.
+const ANIMATION_LOOKAHEAD: usize = 100_000;
+
+/// Solve the puzzle with each of the algorithms at once, animating them side
+/// by side until the user quits.
+///
+/// Returns the solution found by the first algorithm.
+fn animate(
+    puzzle: Puzzle,
+    algorithms: &[AlgorithmKind],
+    trace: Option<PathBuf>,
+    delay_ms: Option<u64>,
+    max_steps: Option<u64>,
+) -> Result<(BaseSolution, Outcome)> {
+    // The TUI cancels the token when the user quits, which stops every solver.
+    let cancel = CancellationToken::new();
+    let mut lanes = Vec::new();
+    let mut handles = Vec::new();
+    for &algorithm in algorithms {
+        let shared = SharedTrace::bounded(ANIMATION_LOOKAHEAD);
+        lanes.push(Lane::new(Some(algorithm.to_string()), puzzle.clone(), shared.clone()));
+
+        let solution = TuiSolution::new(puzzle.clone(), shared, max_steps);
+        let trace = trace.clone();
+        let cancel = cancel.clone();
+        handles.push(thread::spawn(move || {
+            let (solution, outcome) = solve(algorithm, solution, trace.as_deref(), Some(&cancel))?;
+            Ok((solution.into_base(), outcome))
+        }));
+    }
+
+    let result = Animation::init(lanes, cancel.clone(), delay_ms).run();
+    // The animation may also have ended by failing, in which case the solvers still
+    // need stopping.
+    cancel.cancel();
+
+    let mut solutions = Vec::new();
+    for handle in handles {
+        solutions.push(handle.join().unwrap_or_else(|_| Err(anyhow!("solver panicked"))));
+    }
+
+    result?;
+    solutions.into_iter().next().ok_or_else(|| anyhow!("no algorithms to animate"))?
+}
+
+/// How often the spinner is redrawn.
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Draws a spinner on stderr with the solve's progress, once it has been
+/// running long enough to seem slow.
+fn spinner() -> impl FnMut(&Progress) + Send + 'static {
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    const DELAY: Duration = Duration::from_millis(500);
+    let mut frame = 0;
+    move |progress| {
+        if progress.elapsed < DELAY {
+            return;
+        }
+        let Progress { steps, depth, elapsed } = progress;
+        let spinner = FRAMES[frame % FRAMES.len()];
+        eprint!("\r\x1b[2K{spinner} Solving: {steps} steps, depth {depth}, {:.1}s", elapsed.as_secs_f64());
+        frame += 1;
     }
 }
 
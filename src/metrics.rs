@@ -1,28 +1,34 @@
-use crate::PUZZLE_DIGITS;
-
+/// How much work a solver did, counted as reads and writes of squares.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct Metrics {
     // TODO: track "cycles" (pointer moves)
-    square_edits: [u64; PUZZLE_DIGITS],
-    square_views: [u64; PUZZLE_DIGITS],
+    square_edits: u64,
+    square_views: u64,
 }
 
 impl Metrics {
-    pub fn record_edit(&mut self, idx: usize) {
-        self.square_edits[idx] += 1;
+    pub fn record_edit(&mut self) {
+        self.square_edits += 1;
     }
 
-    pub fn record_view(&mut self, idx: usize) {
-        self.square_views[idx] += 1;
+    pub fn record_view(&mut self) {
+        self.square_views += 1;
     }
 
-    pub fn write_logs(&self) {
-        log::info!("Total Square Edits: {}", self.square_edits.into_iter().sum::<u64>());
-        log::info!("Total Square Views: {}", self.square_views.into_iter().sum::<u64>());
+    pub fn record_views(&mut self, count: usize) {
+        self.square_views += count as u64;
+    }
+
+    pub fn total_edits(&self) -> u64 {
+        self.square_edits
+    }
+
+    pub fn total_views(&self) -> u64 {
+        self.square_views
     }
-}
 
-impl Default for Metrics {
-    fn default() -> Self {
-        Self { square_edits: std::array::from_fn(|_| 0), square_views: std::array::from_fn(|_| 0) }
+    pub fn write_logs(&self) {
+        log::info!("Total Square Edits: {}", self.total_edits());
+        log::info!("Total Square Views: {}", self.total_views());
     }
 }
@@ -1,42 +1,80 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::fmt;
-use std::ops::Range;
 use std::str::FromStr;
 
 use anyhow::{Error, Result, anyhow};
 
-use crate::PUZZLE_DIGITS;
+use crate::util::{DigitChar, parse_digit_char};
 
-#[derive(Debug, Eq, PartialEq)]
+pub mod cage;
+pub mod constraint;
+pub mod dimensions;
+pub mod regions;
+pub use cage::Cage;
+pub use constraint::{Constraint, Standard, Variant};
+pub use dimensions::Dimensions;
+pub use regions::Regions;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Puzzle {
-    pub data: [Option<u8>; PUZZLE_DIGITS],
+    pub dimensions: Dimensions,
+    /// The value of each square, in reading order.
+    pub data: Vec<Option<u8>>,
     /// Optional tracking of the cells which were initially filled, restricting
     /// edits to them.
-    pub initially_filled: Option<[bool; PUZZLE_DIGITS]>,
+    pub initially_filled: Option<Vec<bool>>,
+    /// The regions which may not repeat a digit, which are the boxes unless
+    /// this is a Jigsaw Sudoku.
+    pub regions: Regions,
+    /// The cages of a Killer Sudoku, which are empty for any other puzzle.
+    pub cages: Vec<Cage>,
+    /// The extra rules the puzzle follows, on top of the standard ones.
+    pub variants: Vec<Variant>,
 }
 
 impl Puzzle {
+    /// An empty puzzle of the given shape.
+    pub fn empty(dimensions: Dimensions) -> Self {
+        Self {
+            dimensions,
+            data: vec![None; dimensions.squares()],
+            initially_filled: None,
+            regions: Regions::boxes(dimensions),
+            cages: Vec::new(),
+            variants: Vec::new(),
+        }
+    }
+
     pub fn get(&self, idx: usize) -> Option<u8> {
         self.data[idx]
     }
 
     pub fn set(&mut self, idx: usize, value: Option<u8>) {
-        if !self.initially_filled.map(|filled| filled[idx]).unwrap_or_default() {
+        if !self.is_initially_filled(idx) {
             self.data[idx] = value;
         }
     }
 
     pub fn track_initial(&mut self) {
-        self.initially_filled = Some(std::array::from_fn(|idx| self.data[idx].is_some()));
+        self.initially_filled = Some(self.data.iter().map(Option::is_some).collect());
+    }
+
+    /// Returns true if initial squares are being tracked and the square at
+    /// `idx` is one of them.
+    pub fn is_initially_filled(&self, idx: usize) -> bool {
+        self.initially_filled.as_ref().map(|filled| filled[idx]).unwrap_or_default()
     }
 
-    /// Render the puzzle to a single line of 81 digits.
+    /// Render the puzzle to a single line with a character for each square.
+    ///
+    /// Digits above 9 are written as letters, see [`DigitChar`].
     pub fn serialize(&self) -> String {
-        let mut buffer = String::with_capacity(PUZZLE_DIGITS);
-        for idx in 0..PUZZLE_DIGITS {
-            let char = match self.data[idx] {
-                // We only ever store digits in data, so both the u32 cast and unwrap should be safe.
-                Some(digit) => char::from_digit(digit as u32, 10).unwrap(),
+        let mut buffer = String::with_capacity(self.data.len());
+        for digit in &self.data {
+            let char = match digit {
+                // We only ever store digits no higher than the puzzle size in data, which is at
+                // most 25, so this unwrap should be safe.
+                Some(digit) => digit.digit_char().unwrap(),
                 None => '0',
             };
             buffer.push(char);
@@ -45,7 +83,7 @@ impl Puzzle {
     }
 
     pub fn prev_empty(&self, index: usize) -> Option<usize> {
-        (0..index).into_iter().rev().find(|&i| self.data[i].is_none())
+        (0..index).rev().find(|&i| self.data[i].is_none())
     }
 
     pub fn next_empty(&self, index: usize) -> Option<usize> {
@@ -57,50 +95,70 @@ impl Puzzle {
         self.data.iter().all(Option::is_some)
     }
 
-    /// Returns a list of invalid square indices if the puzzle state is invalid.
-    pub fn validate(&self) -> Result<(), HashSet<usize>> {
-        fn pass(
-            puzzle: &Puzzle,
-            invalid_squares: &mut HashSet<usize>,
-            irange: Range<usize>,
-            jrange: Range<usize>,
-            calculate_index: impl Fn(usize, usize) -> usize,
-            seen: &mut HashMap<u8, Vec<usize>>,
-            clear_seen_outer_loop: bool,
-        ) {
-            for i in irange {
-                for j in jrange.clone() {
-                    let index = calculate_index(i, j);
-                    if let Some(value) = puzzle.data[index] {
-                        seen.entry(value).or_insert_with(|| Vec::new()).push(index);
-                    }
-                }
+    /// The rules which the puzzle's digits must follow.
+    pub fn constraints(&self) -> impl Iterator<Item = &dyn Constraint> {
+        std::iter::once(&Standard as &dyn Constraint).chain(self.extra_constraints())
+    }
 
-                invalid_squares.extend(seen.values().filter(|idxs| idxs.len() > 1).flatten());
-                if clear_seen_outer_loop {
-                    seen.clear();
-                }
-            }
+    /// The rules which the puzzle's digits must follow besides the
+    /// [`Standard`] ones, i.e. its cages and variants.
+    pub fn extra_constraints(&self) -> impl Iterator<Item = &dyn Constraint> {
+        let cages = self.cages.iter().map(|cage| cage as &dyn Constraint);
+        let variants = self.variants.iter().map(|variant| variant as &dyn Constraint);
+        cages.chain(variants)
+    }
 
-            // If we don't clear seen after each outer loop iter, we ought to do it now.
-            if !clear_seen_outer_loop {
-                seen.clear();
+    /// Every square whose digit is compared with the digit in the square at
+    /// `index` by one of the puzzle's constraints.
+    pub fn peers(&self, index: usize) -> HashSet<usize> {
+        self.constraints().flat_map(|constraint| constraint.peers(self, index)).collect()
+    }
+
+    /// Parse a line describing one of the puzzle's rules beyond its digits:
+    /// - A Jigsaw Sudoku region map like `regions 111222333111...`, with a
+    ///   label for each square, see [`Regions::parse`].
+    /// - A Killer Sudoku cage like `cage 10 r1c1 r1c2`.
+    /// - A variant like `variant diagonal`.
+    pub fn parse_rule(&mut self, line: &str) -> Result<()> {
+        if let Some(regions) = line.strip_prefix("regions ") {
+            self.regions = Regions::parse(regions, self.dimensions)?;
+        } else if let Some(cage) = line.strip_prefix("cage ") {
+            let cage = Cage::parse(cage, self.dimensions)?;
+            if let Some(&square) = cage.squares.iter().find(|&&square| self.cage(square).is_some()) {
+                return Err(anyhow!("square {} is in more than one cage", self.dimensions.square_name(square)));
+            }
+            self.cages.push(cage);
+        } else if let Some(variant) = line.strip_prefix("variant ") {
+            let variant = Variant::from_str(variant.trim())?;
+            if !self.variants.contains(&variant) {
+                self.variants.push(variant);
             }
+        } else {
+            return Err(anyhow!("expected regions, a cage or a variant, found {line:?}"));
         }
+        Ok(())
+    }
 
-        let mut invalid_squares = HashSet::new();
-        let mut seen = HashMap::new();
+    /// Render each of the puzzle's rules beyond its digits as it is parsed by
+    /// [`Puzzle::parse_rule`].
+    pub fn serialize_rules(&self) -> Vec<String> {
+        let is_jigsaw = self.regions != Regions::boxes(self.dimensions);
+        let regions = is_jigsaw.then(|| format!("regions {}", self.regions.serialize()));
+        let cages = self.cages.iter().map(|cage| format!("cage {}", cage.serialize(self.dimensions)));
+        let variants = self.variants.iter().map(|variant| format!("variant {variant}"));
+        regions.into_iter().chain(cages).chain(variants).collect()
+    }
 
-        // Validate by row.
-        pass(self, &mut invalid_squares, 0..9, 0..9, |i, j| i * 9 + j, &mut seen, true);
-        // Validate by column.
-        pass(self, &mut invalid_squares, 0..9, 0..9, |i, j| j * 9 + i, &mut seen, true);
+    /// The Killer Sudoku cage containing the square at `index`, if any.
+    pub fn cage(&self, index: usize) -> Option<&Cage> {
+        self.cages.iter().find(|cage| cage.squares.contains(&index))
+    }
 
-        for rowr in [0..3, 3..6, 6..9] {
-            for colr in [0..3, 3..6, 6..9] {
-                // Validate by box.
-                pass(self, &mut invalid_squares, rowr.clone(), colr, |i, j| i * 9 + j, &mut seen, false);
-            }
+    /// Returns a list of invalid square indices if the puzzle state is invalid.
+    pub fn validate(&self) -> Result<(), HashSet<usize>> {
+        let mut invalid_squares = HashSet::new();
+        for constraint in self.constraints() {
+            constraint.validate(self, &mut invalid_squares);
         }
 
         if !invalid_squares.is_empty() {
@@ -113,53 +171,146 @@ impl Puzzle {
 
 impl Default for Puzzle {
     fn default() -> Self {
-        Self { data: std::array::from_fn(|_| None), initially_filled: None }
+        Self::empty(Dimensions::STANDARD)
     }
 }
 
+/// The words which start each kind of line parsed by [`Puzzle::parse_rule`].
+const RULES: [&str; 3] = ["regions", "cage", "variant"];
+
+/// The characters which mean "empty square", besides `0`.
+const EMPTY: [char; 6] = ['.', '*', '_', 'x', 'X', ' '];
+
 impl FromStr for Puzzle {
     type Err = Error;
 
+    /// Parse a puzzle from its grid, which also determines the size of the
+    /// puzzle. The grid may be written:
+    /// - On a single line with a character for each square.
+    /// - With a line for each row, optionally split into boxes by `|` and
+    ///   lines of `-` like the output of [`Puzzle`]'s `Display`.
+    ///
+    /// Squares may be separated by whitespace. `0`, `.`, `*`, `_` and `x` all
+    /// mean an empty square, as does a space when each row has a character
+    /// for every square. Digits above 9 are written as letters in either case,
+    /// see [`DigitChar`]. Blank lines and lines starting with `#` are ignored.
+    ///
+    /// The grid may be followed by a line for each of the puzzle's other rules,
+    /// see [`Puzzle::parse_rule`].
     fn from_str(input: &str) -> Result<Self> {
-        if input.len() != PUZZLE_DIGITS {
-            return Err(anyhow!("puzzle must have {PUZZLE_DIGITS} digits"));
+        let mut rows = Vec::new();
+        let mut rules = Vec::new();
+        for line in input.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if trimmed.split_whitespace().next().is_some_and(|word| RULES.contains(&word)) {
+                rules.push(trimmed);
+            } else if !is_separator(line) {
+                rows.push(line);
+            }
         }
 
-        let mut puzzle = Puzzle::default();
-
-        for (idx, char) in input.chars().enumerate() {
-            match char.to_digit(10) {
-                // We accept both '0' and '.' to mean "empty square"
+        let (dimensions, cells) = grid_cells(&rows)?;
+        let size = dimensions.size();
+        let mut puzzle = Puzzle::empty(dimensions);
+        for (idx, char) in cells.into_iter().enumerate() {
+            if EMPTY.contains(&char) {
+                continue;
+            }
+            match parse_digit_char(char) {
                 Some(0) => {}
-                None if char == '.' => {}
-                Some(digit) => {
-                    // This cast to u8 is safe since digit will only ever be 0-9.
-                    puzzle.data[idx] = Some(digit as u8);
+                Some(digit) if digit as usize <= size => {
+                    puzzle.data[idx] = Some(digit);
+                }
+                _ => {
+                    let square = dimensions.square_name(idx);
+                    return Err(anyhow!(
+                        "character {char} at {square} is not a digit from 1-{size} or an empty square"
+                    ));
                 }
-                None => return Err(anyhow!("character {char} at index {idx} is not a digit or period")),
             }
         }
 
+        for rule in rules {
+            puzzle.parse_rule(rule)?;
+        }
+
         Ok(puzzle)
     }
 }
 
+/// Returns true if the line only separates rows of boxes, like `---+---`.
+fn is_separator(line: &str) -> bool {
+    line.contains(['-', '=']) && line.chars().all(|char| "-=+*| \t".contains(char))
+}
+
+/// Find the shape of the puzzle written on the given grid lines, and the
+/// character for each of its squares.
+fn grid_cells(rows: &[&str]) -> Result<(Dimensions, Vec<char>)> {
+    let squares = |row: &str| row.chars().filter(|&char| char != '|').collect::<Vec<_>>();
+    let separated = |row: &str| squares(row).into_iter().filter(|char| !char.is_whitespace()).collect::<Vec<_>>();
+    match rows {
+        [] => Err(anyhow!("puzzle is missing its grid")),
+        [row] => {
+            let cells = separated(row);
+            match Dimensions::from_square_count(cells.len()) {
+                Ok(dimensions) => Ok((dimensions, cells)),
+                // The spaces may be empty squares rather than separators.
+                Err(error) => {
+                    let cells = squares(row);
+                    let dimensions = Dimensions::from_square_count(cells.len()).map_err(|_| error)?;
+                    Ok((dimensions, cells))
+                }
+            }
+        }
+        rows => {
+            let dimensions = Dimensions::from_size(rows.len())?;
+            let size = dimensions.size();
+            let mut cells = Vec::with_capacity(dimensions.squares());
+            for (number, row) in rows.iter().enumerate() {
+                let mut row_cells = separated(row);
+                if row_cells.len() != size {
+                    // The spaces may be empty squares rather than separators, in which case any at
+                    // the end of the row may have been trimmed.
+                    row_cells = squares(row.trim_end());
+                    if row_cells.len() < size {
+                        row_cells.resize(size, ' ');
+                    }
+                }
+                if row_cells.len() != size {
+                    return Err(anyhow!("row {} has {} squares, not {size}", number + 1, row_cells.len()));
+                }
+                cells.extend(row_cells);
+            }
+            Ok((dimensions, cells))
+        }
+    }
+}
+
 impl fmt::Display for Puzzle {
+    /// Render the puzzle's digits as a grid split into boxes, which can be
+    /// parsed back with [`Puzzle::from_str`]. Its cages, jigsaw regions and
+    /// variants aren't included, so see [`Puzzle::serialize_rules`] for them.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // TODO: Clean this code up.
-        let horizontal_bar = str::repeat("-", 13);
+        let Dimensions { box_rows, box_cols } = self.dimensions;
+        let size = self.dimensions.size();
+        // One character per square, plus a bar on either side of each box.
+        let horizontal_bar = str::repeat("-", size + size / box_cols + 1);
         for (idx, digit) in self.data.iter().enumerate() {
-            if idx % (9 * 3) == 0 {
+            let (row, col) = self.dimensions.position(idx);
+            if col == 0 && row % box_rows == 0 {
                 writeln!(f, "{horizontal_bar}")?;
             }
-            if idx % 3 == 0 {
+            if col % box_cols == 0 {
                 write!(f, "|")?;
             }
-            match digit {
-                Some(digit) => write!(f, "{digit}")?,
+            match digit.and_then(DigitChar::digit_char) {
+                Some(char) => write!(f, "{char}")?,
                 None => write!(f, ".")?,
             }
-            if idx % 9 == 9 - 1 {
+            if col == size - 1 {
                 writeln!(f, "|")?;
             }
         }
@@ -167,3 +318,165 @@ impl fmt::Display for Puzzle {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+    use proptest::{collection, option, sample};
+
+    use super::*;
+
+    #[test]
+    fn test_from_str_sizes() {
+        let puzzle = Puzzle::from_str("1.3.....2..4...1").unwrap();
+        assert_eq!(puzzle.dimensions, Dimensions { box_rows: 2, box_cols: 2 });
+        assert_eq!(puzzle.get(2), Some(3));
+
+        let puzzle = Puzzle::from_str(&format!("G{}a", ".".repeat(254))).unwrap();
+        assert_eq!(puzzle.dimensions, Dimensions { box_rows: 4, box_cols: 4 });
+        assert_eq!(puzzle.get(0), Some(16));
+        assert_eq!(puzzle.get(255), Some(10));
+        assert_eq!(puzzle.serialize(), format!("G{}A", "0".repeat(254)));
+
+        // 5 is too high for a 4x4 puzzle, and 80 squares isn't a supported size.
+        assert!(Puzzle::from_str("5...............").is_err());
+        assert!(Puzzle::from_str(&".".repeat(80)).is_err());
+    }
+
+    #[test]
+    fn test_validate_rectangular_boxes() {
+        // Boxes in a 6x6 puzzle are 2 rows tall and 3 columns wide, so r1c2 shares a
+        // box with r0c0 but r2c4 doesn't.
+        let mut puzzle = Puzzle::empty(Dimensions { box_rows: 2, box_cols: 3 });
+        puzzle.set(0, Some(1));
+        puzzle.set(2 * 6 + 4, Some(1));
+        assert!(puzzle.validate().is_ok());
+
+        puzzle.set(6 + 1, Some(1));
+        assert_eq!(puzzle.validate(), Err(HashSet::from([0, 7])));
+    }
+
+    #[test]
+    fn test_from_str_cages() {
+        let puzzle = Puzzle::from_str("1...............\ncage 3 r1c1 r1c2\n\ncage 4 r4c4").unwrap();
+        assert_eq!(puzzle.cages, [Cage { sum: 3, squares: vec![0, 1] }, Cage { sum: 4, squares: vec![15] }]);
+        assert_eq!(puzzle.cage(1), puzzle.cages.first());
+        assert_eq!(puzzle.cage(2), None);
+
+        assert!(Puzzle::from_str("1...............\ncage 3 r1c1 r1c2\ncage 4 r1c2").is_err());
+        assert!(Puzzle::from_str("1...............\n3 r1c1 r1c2").is_err());
+    }
+
+    #[test]
+    fn test_validate_cages() {
+        let mut puzzle = Puzzle::from_str("................\ncage 3 r1c1 r1c2").unwrap();
+        puzzle.set(0, Some(1));
+        assert!(puzzle.validate().is_ok());
+
+        puzzle.set(1, Some(3));
+        assert_eq!(puzzle.validate(), Err(HashSet::from([0, 1])));
+    }
+
+    #[test]
+    fn test_variants() {
+        let mut puzzle = Puzzle::from_str(&format!("{}\nvariant diagonal\nvariant anti-king", ".".repeat(81))).unwrap();
+        assert_eq!(puzzle.variants, [Variant::Diagonal, Variant::AntiKing]);
+        assert_eq!(puzzle.serialize_rules(), ["variant diagonal", "variant anti-king"]);
+        assert!(Puzzle::from_str(&format!("{}\nvariant sideways", ".".repeat(81))).is_err());
+
+        // r1c1 and r2c2 share a box anyway, so only the diagonal catches r1c1 and r9c9.
+        puzzle.set(0, Some(1));
+        puzzle.set(80, Some(1));
+        assert_eq!(puzzle.validate(), Err(HashSet::from([0, 80])));
+        assert!(puzzle.peers(0).contains(&80));
+    }
+
+    #[test]
+    fn test_regions() {
+        // The regions are mostly the columns, but the first two swap r2c1 and r2c2.
+        let mut puzzle = Puzzle::from_str("................\nregions 1234 2134 1234 1234").unwrap();
+        assert_eq!(puzzle.serialize_rules(), ["regions 1234213412341234"]);
+
+        // r1c3 and r2c4 share a box, but not a region.
+        puzzle.set(2, Some(1));
+        puzzle.set(7, Some(1));
+        assert!(puzzle.validate().is_ok());
+
+        // r2c2 and r3c1 share a region, but not a box.
+        puzzle.set(5, Some(2));
+        puzzle.set(8, Some(2));
+        assert_eq!(puzzle.validate(), Err(HashSet::from([5, 8])));
+    }
+
+    #[test]
+    fn test_from_str_grids() {
+        let expected = Puzzle::from_str("1.3.....2..4...1").unwrap();
+        let grids = [
+            "1.3.\n....\n2..4\n...1",
+            "# A comment\n\n1 * 3 *\n_ _ _ _\n2 x x 4\nX 0 . 1\n",
+            "+--+--+\n|1.|3.|\n|..|..|\n+--+--+\n|2.|.4|\n|..|.1|\n+--+--+",
+            "1 3 \n....\n2  4\n   1",
+        ];
+        for grid in grids {
+            assert_eq!(Puzzle::from_str(grid).unwrap(), expected, "{grid:?}");
+        }
+
+        let puzzle = Puzzle::from_str("1.3.\n....\n2..4\n...1\n# Rules follow\nvariant diagonal").unwrap();
+        assert_eq!(puzzle.variants, [Variant::Diagonal]);
+
+        // Rows must all be the same length, and there must be as many of them as columns.
+        assert!(Puzzle::from_str("1.3.\n.....\n2..4\n...1").is_err());
+        assert!(Puzzle::from_str("1.3.\n....\n2..4").is_err());
+        assert!(Puzzle::from_str("# Nothing but a comment").is_err());
+    }
+
+    #[test]
+    /// Only the digits are displayed, so this only round trips classic puzzles.
+    fn test_display_round_trip() {
+        let puzzles = [
+            "1.3.....2..4...1".to_string(),
+            format!("123456{}", ".".repeat(30)),
+            "050703060007000800000816000000030000005000100730040086906000204840572093000409000".to_string(),
+            format!("9ABC{}P", ".".repeat(620)),
+        ];
+        for text in puzzles {
+            let puzzle = Puzzle::from_str(&text).unwrap();
+            assert_eq!(Puzzle::from_str(&puzzle.to_string()).unwrap(), puzzle);
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let puzzle = Puzzle::from_str("1.3.....2..4...1").unwrap();
+        assert_eq!(puzzle.to_string(), "-------\n|1.|3.|\n|..|..|\n-------\n|2.|.4|\n|..|.1|\n-------\n");
+    }
+
+    /// A puzzle of any supported size, with each square either empty or
+    /// holding any of its digits.
+    fn any_puzzle() -> impl Strategy<Value = Puzzle> {
+        sample::select(Dimensions::SUPPORTED.to_vec()).prop_flat_map(|dimensions| {
+            // This cast to u8 is safe since puzzles are never more than 25 squares across.
+            let digits = option::of(1..=dimensions.size() as u8);
+            collection::vec(digits, dimensions.squares())
+                .prop_map(move |data| Puzzle { data, ..Puzzle::empty(dimensions) })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_serialize_round_trip(puzzle in any_puzzle()) {
+            prop_assert_eq!(&Puzzle::from_str(&puzzle.serialize()).unwrap(), &puzzle);
+            prop_assert_eq!(&Puzzle::from_str(&puzzle.to_string()).unwrap(), &puzzle);
+        }
+
+        #[test]
+        fn test_from_str_arbitrary(
+            text in "([0-9A-Za-z.*_ |+=#-]{0,30}\n){0,30}(regions [a-z ]{0,40}|cage [0-9rc ]{0,20}|variant [a-z-]{0,15})?",
+        ) {
+            // Anything may be rejected, but nothing may panic, even once parsed.
+            if let Ok(puzzle) = Puzzle::from_str(&text) {
+                let _ = (puzzle.to_string(), puzzle.serialize_rules(), puzzle.validate());
+            }
+        }
+    }
+}
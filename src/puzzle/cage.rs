@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, anyhow};
+
+use crate::puzzle::constraint::{Constraint, find_repeats};
+use crate::puzzle::{Dimensions, Puzzle};
+
+/// A group of squares from Killer Sudoku, whose digits must not repeat and
+/// must add up to `sum`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cage {
+    pub sum: u32,
+    /// The squares in the cage, in reading order.
+    pub squares: Vec<usize>,
+}
+
+impl Cage {
+    /// Parse a cage from its sum followed by its squares, e.g. `10 r1c1 r1c2`.
+    pub fn parse(text: &str, dimensions: Dimensions) -> Result<Self> {
+        let mut parts = text.split_whitespace();
+        let sum = parts.next().ok_or_else(|| anyhow!("missing cage sum"))?;
+        let sum = sum.parse().map_err(|_| anyhow!("invalid cage sum {sum}"))?;
+        let mut squares = parts.map(|square| dimensions.parse_square(square)).collect::<Result<Vec<_>>>()?;
+        squares.sort_unstable();
+
+        if squares.is_empty() {
+            return Err(anyhow!("cage has no squares"));
+        }
+        if squares.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(anyhow!("cage contains the same square twice"));
+        }
+        if !reachable(dimensions.size(), &[], squares.len(), sum) {
+            return Err(anyhow!("{} different digits can't add up to {sum}", squares.len()));
+        }
+        Ok(Self { sum, squares })
+    }
+
+    /// Render the cage as it is parsed by [`Cage::parse`].
+    pub fn serialize(&self, dimensions: Dimensions) -> String {
+        let squares = self.squares.iter().map(|&square| format!(" {}", dimensions.square_name(square)));
+        format!("{}{}", self.sum, squares.collect::<String>())
+    }
+
+    /// The top-left square of the cage, where its sum is shown.
+    pub fn first(&self) -> usize {
+        self.squares[0]
+    }
+
+    /// Whether the digits already in the cage, along with the `placed` digit if
+    /// given, could still add up to the sum.
+    fn is_reachable(&self, puzzle: &Puzzle, placed: Option<(usize, u8)>) -> bool {
+        let mut digits = Vec::new();
+        let mut empty = 0;
+        for &square in &self.squares {
+            match placed.filter(|&(index, _)| index == square).map(|(_, digit)| digit).or(puzzle.get(square)) {
+                Some(digit) => digits.push(digit),
+                None => empty += 1,
+            }
+        }
+        reachable(puzzle.dimensions.size(), &digits, empty, self.sum)
+    }
+}
+
+/// Whether `empty` more squares could be filled with digits from 1 to `size`,
+/// which aren't among `digits`, such that the total is `sum`.
+fn reachable(size: usize, digits: &[u8], empty: usize, sum: u32) -> bool {
+    // This cast to u8 is safe since puzzles are never more than 25 squares across.
+    let unused: Vec<u32> = (1..=size as u8).filter(|digit| !digits.contains(digit)).map(u32::from).collect();
+    if unused.len() < empty {
+        return false;
+    }
+    let filled: u32 = digits.iter().copied().map(u32::from).sum();
+    let least: u32 = unused[..empty].iter().sum();
+    let most: u32 = unused[unused.len() - empty..].iter().sum();
+    (filled + least..=filled + most).contains(&sum)
+}
+
+impl Constraint for Cage {
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool {
+        if !self.squares.contains(&index) {
+            return true;
+        }
+        let repeats = self.squares.iter().any(|&square| square != index && puzzle.get(square) == Some(digit));
+        !repeats && self.is_reachable(puzzle, Some((index, digit)))
+    }
+
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>) {
+        find_repeats(puzzle, self.squares.iter().copied(), invalid_squares);
+        if !self.is_reachable(puzzle, None) {
+            invalid_squares.extend(self.squares.iter().filter(|&&square| puzzle.get(square).is_some()));
+        }
+    }
+
+    fn peers(&self, _puzzle: &Puzzle, index: usize) -> Vec<usize> {
+        if !self.squares.contains(&index) {
+            return Vec::new();
+        }
+        self.squares.iter().copied().filter(|&square| square != index).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let cage = Cage::parse("10 r1c2 r1c1", Dimensions::STANDARD).unwrap();
+        assert_eq!(cage, Cage { sum: 10, squares: vec![0, 1] });
+        assert_eq!(cage.serialize(Dimensions::STANDARD), "10 r1c1 r1c2");
+
+        assert!(Cage::parse("", Dimensions::STANDARD).is_err());
+        assert!(Cage::parse("10", Dimensions::STANDARD).is_err());
+        assert!(Cage::parse("10 r1c1 r1c1", Dimensions::STANDARD).is_err());
+        assert!(Cage::parse("10 r1c10", Dimensions::STANDARD).is_err());
+        // Two different digits add up to at least 3 and at most 17.
+        assert!(Cage::parse("2 r1c1 r1c2", Dimensions::STANDARD).is_err());
+        assert!(Cage::parse("18 r1c1 r1c2", Dimensions::STANDARD).is_err());
+    }
+
+    #[test]
+    fn test_allows() {
+        let mut puzzle = Puzzle::default();
+        let cage = Cage { sum: 10, squares: vec![0, 1, 2] };
+        puzzle.set(0, Some(1));
+
+        // 1 + 2 + 7 is fine, but 1 + 8 leaves nothing for the last square.
+        assert!(cage.allows(&puzzle, 1, 2));
+        assert!(!cage.allows(&puzzle, 1, 8));
+        assert!(!cage.allows(&puzzle, 1, 1));
+        // Squares outside the cage are unaffected.
+        assert!(cage.allows(&puzzle, 3, 9));
+
+        puzzle.set(1, Some(2));
+        puzzle.set(2, Some(6));
+        let mut invalid_squares = HashSet::new();
+        cage.validate(&puzzle, &mut invalid_squares);
+        assert_eq!(invalid_squares, HashSet::from([0, 1, 2]));
+    }
+}
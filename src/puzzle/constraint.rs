@@ -0,0 +1,195 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Error, Result, anyhow};
+
+use crate::puzzle::{Dimensions, Puzzle};
+
+pub mod anti_chess;
+pub mod diagonal;
+pub mod non_consecutive;
+pub mod windoku;
+pub use anti_chess::{AntiKing, AntiKnight};
+pub use diagonal::Diagonal;
+pub use non_consecutive::NonConsecutive;
+pub use windoku::Windoku;
+
+/// A rule which the digits in a puzzle must follow.
+///
+/// Every puzzle follows the [`Standard`] rules, along with any rules of its
+/// variant, see [`Puzzle::constraints`].
+pub trait Constraint: fmt::Debug + Send + Sync {
+    /// Returns true if `digit` could go in the square at `index` without
+    /// breaking this rule, given the digits in every other square.
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool;
+
+    /// Add every square which is currently breaking this rule to
+    /// `invalid_squares`.
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>);
+
+    /// The squares whose digits this rule compares with the digit in the
+    /// square at `index`.
+    fn peers(&self, puzzle: &Puzzle, index: usize) -> Vec<usize>;
+
+    /// How many squares [`Constraint::peers`] returns, which solvers count
+    /// for their [`Metrics`](crate::metrics::Metrics) on every check, so
+    /// implementors should avoid listing them if they can.
+    fn peer_count(&self, puzzle: &Puzzle, index: usize) -> usize {
+        self.peers(puzzle, index).len()
+    }
+}
+
+/// Selects one of the variant [`Constraint`]s, which a puzzle can follow any
+/// number of on top of the [`Standard`] rules.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Variant {
+    /// Neither long diagonal may repeat a digit (X-Sudoku)
+    Diagonal,
+    /// Squares a chess knight's move apart may not hold the same digit
+    AntiKnight,
+    /// Squares a chess king's move apart may not hold the same digit
+    AntiKing,
+    /// Orthogonally adjacent squares may not hold consecutive digits
+    NonConsecutive,
+    /// Four extra boxes, each one square in from a corner of a box, may not
+    /// repeat a digit
+    Windoku,
+}
+
+impl Variant {
+    fn constraint(&self) -> &'static dyn Constraint {
+        match self {
+            Self::Diagonal => &Diagonal,
+            Self::AntiKnight => &AntiKnight,
+            Self::AntiKing => &AntiKing,
+            Self::NonConsecutive => &NonConsecutive,
+            Self::Windoku => &Windoku,
+        }
+    }
+
+    const ALL: [Self; 5] = [Self::Diagonal, Self::AntiKnight, Self::AntiKing, Self::NonConsecutive, Self::Windoku];
+}
+
+impl Constraint for Variant {
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool {
+        self.constraint().allows(puzzle, index, digit)
+    }
+
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>) {
+        self.constraint().validate(puzzle, invalid_squares)
+    }
+
+    fn peers(&self, puzzle: &Puzzle, index: usize) -> Vec<usize> {
+        self.constraint().peers(puzzle, index)
+    }
+
+    fn peer_count(&self, puzzle: &Puzzle, index: usize) -> usize {
+        self.constraint().peer_count(puzzle, index)
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Diagonal => "diagonal",
+            Self::AntiKnight => "anti-knight",
+            Self::AntiKing => "anti-king",
+            Self::NonConsecutive => "non-consecutive",
+            Self::Windoku => "windoku",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Variant {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        Self::ALL.into_iter().find(|variant| variant.to_string() == name).ok_or_else(|| {
+            let names: Vec<_> = Self::ALL.iter().map(Variant::to_string).collect();
+            anyhow!("unknown variant {name}, expected one of {}", names.join(", "))
+        })
+    }
+}
+
+/// Every row, column and region must not repeat a digit, where the regions
+/// are usually the boxes.
+#[derive(Clone, Copy, Debug)]
+pub struct Standard;
+
+impl Constraint for Standard {
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool {
+        let dimensions = puzzle.dimensions;
+        let (row, col) = dimensions.position(index);
+
+        let mut lines = (0..dimensions.size()).flat_map(|i| [dimensions.index(row, i), dimensions.index(i, col)]);
+        !lines.any(|peer| peer != index && puzzle.get(peer) == Some(digit))
+            && !repeats(puzzle, puzzle.regions.region_of(index), index, digit)
+    }
+
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>) {
+        let dimensions = puzzle.dimensions;
+        let size = dimensions.size();
+        for i in 0..size {
+            find_repeats(puzzle, (0..size).map(|j| dimensions.index(i, j)), invalid_squares);
+            find_repeats(puzzle, (0..size).map(|j| dimensions.index(j, i)), invalid_squares);
+        }
+        for region in puzzle.regions.iter() {
+            find_repeats(puzzle, region.iter().copied(), invalid_squares);
+        }
+    }
+
+    fn peers(&self, puzzle: &Puzzle, index: usize) -> Vec<usize> {
+        let dimensions = puzzle.dimensions;
+        let (row, col) = dimensions.position(index);
+
+        let lines = (0..dimensions.size()).flat_map(|i| [dimensions.index(row, i), dimensions.index(i, col)]);
+        let region = puzzle.regions.region_of(index).iter().copied();
+        let mut peers: Vec<_> = lines.chain(region).filter(|&peer| peer != index).collect();
+        // Squares in the same region as well as the same row or column are only counted
+        // once.
+        peers.sort_unstable();
+        peers.dedup();
+        peers
+    }
+}
+
+/// Returns true if any of `squares` other than `index` holds `digit`.
+pub(crate) fn repeats(puzzle: &Puzzle, squares: &[usize], index: usize, digit: u8) -> bool {
+    squares.iter().any(|&square| square != index && puzzle.get(square) == Some(digit))
+}
+
+/// The squares which are the given (row, column) offsets away from the square
+/// at `index`, skipping any which would be off the grid.
+pub(crate) fn offset_squares(
+    dimensions: Dimensions,
+    index: usize,
+    offsets: &[(isize, isize)],
+) -> impl Iterator<Item = usize> {
+    let (row, col) = dimensions.position(index);
+    let size = dimensions.size();
+    offsets.iter().filter_map(move |&(row_offset, col_offset)| {
+        let row = row.checked_add_signed(row_offset).filter(|&row| row < size)?;
+        let col = col.checked_add_signed(col_offset).filter(|&col| col < size)?;
+        Some(dimensions.index(row, col))
+    })
+}
+
+/// Add every square among `squares` which repeats a digit to
+/// `invalid_squares`.
+pub(crate) fn find_repeats(
+    puzzle: &Puzzle,
+    squares: impl IntoIterator<Item = usize>,
+    invalid_squares: &mut HashSet<usize>,
+) {
+    // The squares holding each digit, which go up to 25.
+    let mut seen: [Vec<usize>; 26] = Default::default();
+    for index in squares {
+        if let Some(digit) = puzzle.get(index) {
+            seen[digit as usize].push(index);
+        }
+    }
+    invalid_squares.extend(seen.into_iter().filter(|indices| indices.len() > 1).flatten());
+}
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::puzzle::Puzzle;
+use crate::puzzle::constraint::{Constraint, offset_squares};
+
+/// The (row, column) offsets of a chess knight's moves.
+const KNIGHT_MOVES: [(isize, isize); 8] = [(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)];
+
+/// The (row, column) offsets of a chess king's moves.
+const KING_MOVES: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// Squares a chess knight's move apart may not hold the same digit.
+#[derive(Clone, Copy, Debug)]
+pub struct AntiKnight;
+
+/// Squares a chess king's move apart may not hold the same digit.
+#[derive(Clone, Copy, Debug)]
+pub struct AntiKing;
+
+impl Constraint for AntiKnight {
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool {
+        allows(puzzle, index, digit, &KNIGHT_MOVES)
+    }
+
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>) {
+        validate(puzzle, invalid_squares, &KNIGHT_MOVES);
+    }
+
+    fn peers(&self, puzzle: &Puzzle, index: usize) -> Vec<usize> {
+        offset_squares(puzzle.dimensions, index, &KNIGHT_MOVES).collect()
+    }
+
+    fn peer_count(&self, puzzle: &Puzzle, index: usize) -> usize {
+        offset_squares(puzzle.dimensions, index, &KNIGHT_MOVES).count()
+    }
+}
+
+impl Constraint for AntiKing {
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool {
+        allows(puzzle, index, digit, &KING_MOVES)
+    }
+
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>) {
+        validate(puzzle, invalid_squares, &KING_MOVES);
+    }
+
+    fn peers(&self, puzzle: &Puzzle, index: usize) -> Vec<usize> {
+        offset_squares(puzzle.dimensions, index, &KING_MOVES).collect()
+    }
+
+    fn peer_count(&self, puzzle: &Puzzle, index: usize) -> usize {
+        offset_squares(puzzle.dimensions, index, &KING_MOVES).count()
+    }
+}
+
+fn allows(puzzle: &Puzzle, index: usize, digit: u8, moves: &[(isize, isize)]) -> bool {
+    offset_squares(puzzle.dimensions, index, moves).all(|square| puzzle.get(square) != Some(digit))
+}
+
+fn validate(puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>, moves: &[(isize, isize)]) {
+    for index in 0..puzzle.data.len() {
+        if let Some(digit) = puzzle.get(index)
+            && !allows(puzzle, index, digit, moves)
+        {
+            invalid_squares.insert(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_anti_knight() {
+        let mut puzzle = Puzzle::default();
+        puzzle.set(40, Some(1));
+
+        // r4c3 is a knight's move from the center, but r4c4 is a king's move.
+        assert!(!AntiKnight.allows(&puzzle, 29, 1));
+        assert!(AntiKnight.allows(&puzzle, 30, 1));
+        assert!(AntiKing.allows(&puzzle, 29, 1));
+        assert!(!AntiKing.allows(&puzzle, 30, 1));
+
+        // Only two knight's moves stay on the grid from a corner.
+        assert_eq!(AntiKnight.peers(&puzzle, 0), [11, 19]);
+
+        puzzle.set(29, Some(1));
+        let mut invalid_squares = HashSet::new();
+        AntiKnight.validate(&puzzle, &mut invalid_squares);
+        assert_eq!(invalid_squares, HashSet::from([29, 40]));
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use crate::puzzle::constraint::{Constraint, find_repeats};
+use crate::puzzle::{Dimensions, Puzzle};
+
+/// Neither of the long diagonals may repeat a digit, as in X-Sudoku.
+#[derive(Clone, Copy, Debug)]
+pub struct Diagonal;
+
+impl Diagonal {
+    /// The squares along each diagonal, from the top down.
+    fn diagonals(dimensions: Dimensions) -> [Vec<usize>; 2] {
+        let size = dimensions.size();
+        [
+            (0..size).map(|i| dimensions.index(i, i)).collect(),
+            (0..size).map(|i| dimensions.index(i, size - 1 - i)).collect(),
+        ]
+    }
+
+    /// Returns true if a square other than `index` along the diagonal which
+    /// has the square in column `col(row)` of each row holds `digit`.
+    fn repeats_along(puzzle: &Puzzle, index: usize, digit: u8, col: impl Fn(usize) -> usize) -> bool {
+        let dimensions = puzzle.dimensions;
+        (0..dimensions.size())
+            .map(|row| dimensions.index(row, col(row)))
+            .any(|square| square != index && puzzle.get(square) == Some(digit))
+    }
+}
+
+impl Constraint for Diagonal {
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool {
+        // This is checked for every candidate while solving, so work out which diagonals
+        // the square is on rather than listing their squares.
+        let size = puzzle.dimensions.size();
+        let (row, col) = puzzle.dimensions.position(index);
+        (row != col || !Self::repeats_along(puzzle, index, digit, |row| row))
+            && (row + col != size - 1 || !Self::repeats_along(puzzle, index, digit, |row| size - 1 - row))
+    }
+
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>) {
+        for diagonal in Self::diagonals(puzzle.dimensions) {
+            find_repeats(puzzle, diagonal, invalid_squares);
+        }
+    }
+
+    fn peers(&self, puzzle: &Puzzle, index: usize) -> Vec<usize> {
+        let diagonals = Self::diagonals(puzzle.dimensions);
+        let on = diagonals.into_iter().filter(|diagonal| diagonal.contains(&index));
+        on.flatten().filter(|&square| square != index).collect()
+    }
+
+    fn peer_count(&self, puzzle: &Puzzle, index: usize) -> usize {
+        let size = puzzle.dimensions.size();
+        let (row, col) = puzzle.dimensions.position(index);
+        let diagonals = usize::from(row == col) + usize::from(row + col == size - 1);
+        diagonals * (size - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_diagonal() {
+        let mut puzzle = Puzzle::default();
+        puzzle.set(0, Some(5));
+
+        // r9c9 shares the main diagonal with r1c1, but r9c1 is only on the other one.
+        assert!(!Diagonal.allows(&puzzle, 80, 5));
+        assert!(Diagonal.allows(&puzzle, 72, 5));
+        assert!(Diagonal.allows(&puzzle, 1, 5));
+
+        puzzle.set(40, Some(5));
+        let mut invalid_squares = HashSet::new();
+        Diagonal.validate(&puzzle, &mut invalid_squares);
+        assert_eq!(invalid_squares, HashSet::from([0, 40]));
+
+        // The center square is on both diagonals.
+        assert_eq!(Diagonal.peers(&puzzle, 40).len(), 16);
+        for index in [0, 1, 40, 72] {
+            assert_eq!(Diagonal.peer_count(&puzzle, index), Diagonal.peers(&puzzle, index).len());
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::HashSet;
+
+use crate::puzzle::Puzzle;
+use crate::puzzle::constraint::{Constraint, offset_squares};
+
+/// The (row, column) offsets of orthogonally adjacent squares.
+const ADJACENT: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Orthogonally adjacent squares may not hold consecutive digits.
+#[derive(Clone, Copy, Debug)]
+pub struct NonConsecutive;
+
+impl Constraint for NonConsecutive {
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool {
+        offset_squares(puzzle.dimensions, index, &ADJACENT)
+            .filter_map(|square| puzzle.get(square))
+            .all(|adjacent| adjacent.abs_diff(digit) != 1)
+    }
+
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>) {
+        for index in 0..puzzle.data.len() {
+            if let Some(digit) = puzzle.get(index)
+                && !self.allows(puzzle, index, digit)
+            {
+                invalid_squares.insert(index);
+            }
+        }
+    }
+
+    fn peers(&self, puzzle: &Puzzle, index: usize) -> Vec<usize> {
+        offset_squares(puzzle.dimensions, index, &ADJACENT).collect()
+    }
+
+    fn peer_count(&self, puzzle: &Puzzle, index: usize) -> usize {
+        offset_squares(puzzle.dimensions, index, &ADJACENT).count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_non_consecutive() {
+        let mut puzzle = Puzzle::default();
+        puzzle.set(0, Some(5));
+
+        assert!(!NonConsecutive.allows(&puzzle, 1, 4));
+        assert!(!NonConsecutive.allows(&puzzle, 9, 6));
+        assert!(NonConsecutive.allows(&puzzle, 1, 7));
+        // Diagonally adjacent squares don't count.
+        assert!(NonConsecutive.allows(&puzzle, 10, 4));
+
+        puzzle.set(1, Some(6));
+        let mut invalid_squares = HashSet::new();
+        NonConsecutive.validate(&puzzle, &mut invalid_squares);
+        assert_eq!(invalid_squares, HashSet::from([0, 1]));
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use crate::puzzle::constraint::{Constraint, find_repeats};
+use crate::puzzle::{Dimensions, Puzzle};
+
+/// Extra boxes, each one square in from the corner of a regular box, may not
+/// repeat a digit.
+///
+/// In a 9x9 puzzle these are the four boxes with their top-left corners at
+/// r2c2, r2c6, r6c2 and r6c6, which are traditionally shaded.
+#[derive(Clone, Copy, Debug)]
+pub struct Windoku;
+
+impl Windoku {
+    /// The squares of each extra box.
+    pub(crate) fn boxes(dimensions: Dimensions) -> Vec<Vec<usize>> {
+        let Dimensions { box_rows, box_cols } = dimensions;
+        let size = dimensions.size();
+        // The extra boxes are separated by a single row or column.
+        let tops = (1..).step_by(box_rows + 1).take_while(|top| top + box_rows <= size);
+        let lefts: Vec<_> = (1..).step_by(box_cols + 1).take_while(|left| left + box_cols <= size).collect();
+
+        tops.flat_map(|top| {
+            lefts.iter().map(move |&left| {
+                (top..top + box_rows)
+                    .flat_map(|row| (left..left + box_cols).map(move |col| dimensions.index(row, col)))
+                    .collect()
+            })
+        })
+        .collect()
+    }
+
+    /// The (row range, column range) of the extra box which the square at
+    /// `row` and `col` lies within, if any.
+    fn box_ranges(dimensions: Dimensions, row: usize, col: usize) -> Option<(Range<usize>, Range<usize>)> {
+        let size = dimensions.size();
+        // Where a row or column lies among the extra boxes' rows or columns, which are
+        // `len` long and start from 1 with a gap of one between each.
+        let span = |position: usize, len: usize| {
+            let start = position.checked_sub(1)? / (len + 1) * (len + 1) + 1;
+            (position < start + len && start + len <= size).then_some(start..start + len)
+        };
+        Some((span(row, dimensions.box_rows)?, span(col, dimensions.box_cols)?))
+    }
+
+    /// The squares other than `index` in the same extra box.
+    fn box_peers(dimensions: Dimensions, index: usize) -> impl Iterator<Item = usize> {
+        let (row, col) = dimensions.position(index);
+        let (rows, cols) = Self::box_ranges(dimensions, row, col).unwrap_or_default();
+        rows.flat_map(move |row| cols.clone().map(move |col| dimensions.index(row, col)))
+            .filter(move |&square| square != index)
+    }
+}
+
+impl Constraint for Windoku {
+    fn allows(&self, puzzle: &Puzzle, index: usize, digit: u8) -> bool {
+        Self::box_peers(puzzle.dimensions, index).all(|square| puzzle.get(square) != Some(digit))
+    }
+
+    fn validate(&self, puzzle: &Puzzle, invalid_squares: &mut HashSet<usize>) {
+        for extra_box in Self::boxes(puzzle.dimensions) {
+            find_repeats(puzzle, extra_box, invalid_squares);
+        }
+    }
+
+    fn peers(&self, puzzle: &Puzzle, index: usize) -> Vec<usize> {
+        Self::box_peers(puzzle.dimensions, index).collect()
+    }
+
+    fn peer_count(&self, puzzle: &Puzzle, index: usize) -> usize {
+        let (row, col) = puzzle.dimensions.position(index);
+        Self::box_ranges(puzzle.dimensions, row, col).map_or(0, |(rows, cols)| rows.len() * cols.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_boxes() {
+        let boxes = Windoku::boxes(Dimensions::STANDARD);
+        assert_eq!(boxes.len(), 4);
+        assert_eq!(boxes[0], [10, 11, 12, 19, 20, 21, 28, 29, 30]);
+        assert_eq!(boxes[3], [50, 51, 52, 59, 60, 61, 68, 69, 70]);
+
+        // A 4x4 puzzle only has room for one.
+        assert_eq!(Windoku::boxes(Dimensions { box_rows: 2, box_cols: 2 }), [vec![5, 6, 9, 10]]);
+    }
+
+    #[test]
+    fn test_box_peers() {
+        for dimensions in
+            [Dimensions::STANDARD, Dimensions { box_rows: 2, box_cols: 3 }, Dimensions::from_size(16).unwrap()]
+        {
+            let boxes = Windoku::boxes(dimensions);
+            let puzzle = Puzzle::empty(dimensions);
+            for index in 0..dimensions.squares() {
+                let mut expected: Vec<_> =
+                    boxes.iter().filter(|extra_box| extra_box.contains(&index)).flatten().copied().collect();
+                expected.retain(|&square| square != index);
+                assert_eq!(
+                    Windoku::box_peers(dimensions, index).collect::<Vec<_>>(),
+                    expected,
+                    "{dimensions:?} {index}"
+                );
+                assert_eq!(Windoku.peer_count(&puzzle, index), expected.len());
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+use anyhow::{Result, anyhow};
+
+use crate::util::{DivRem, parse_digit_char};
+
+/// The shape of a puzzle, which is entirely determined by the shape of its
+/// boxes.
+///
+/// A puzzle has as many rows, columns and boxes as there are squares in a box,
+/// and that is also the number of different digits it uses.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Dimensions {
+    /// The number of rows in each box.
+    pub box_rows: usize,
+    /// The number of columns in each box.
+    pub box_cols: usize,
+}
+
+impl Dimensions {
+    /// The standard 9x9 puzzle.
+    pub const STANDARD: Self = Self { box_rows: 3, box_cols: 3 };
+
+    /// Every puzzle shape we support, from smallest to largest.
+    pub const SUPPORTED: [Self; 6] = [
+        Self { box_rows: 2, box_cols: 2 },
+        Self { box_rows: 2, box_cols: 3 },
+        Self::STANDARD,
+        Self { box_rows: 3, box_cols: 4 },
+        Self { box_rows: 4, box_cols: 4 },
+        Self { box_rows: 5, box_cols: 5 },
+    ];
+
+    /// Find the supported shape for a puzzle with `size` rows and columns.
+    pub fn from_size(size: usize) -> Result<Self> {
+        Self::SUPPORTED.into_iter().find(|dimensions| dimensions.size() == size).ok_or_else(|| {
+            let sizes: Vec<_> = Self::SUPPORTED.iter().map(|dimensions| dimensions.size().to_string()).collect();
+            anyhow!("puzzles must be one of {} squares across, not {size}", sizes.join(", "))
+        })
+    }
+
+    /// Find the supported shape for a puzzle with `count` squares in total.
+    pub fn from_square_count(count: usize) -> Result<Self> {
+        Self::SUPPORTED.into_iter().find(|dimensions| dimensions.squares() == count).ok_or_else(|| {
+            let counts: Vec<_> = Self::SUPPORTED.iter().map(|dimensions| dimensions.squares().to_string()).collect();
+            anyhow!("puzzle must have one of {} digits, not {count}", counts.join(", "))
+        })
+    }
+
+    /// The number of rows and columns, which is also the highest digit.
+    pub fn size(&self) -> usize {
+        self.box_rows * self.box_cols
+    }
+
+    /// The total number of squares.
+    pub fn squares(&self) -> usize {
+        self.size().pow(2)
+    }
+
+    /// The (row, column) of the square at `index`.
+    pub fn position(&self, index: usize) -> (usize, usize) {
+        index.div_rem(self.size())
+    }
+
+    /// The index of the square at `row` and `col`.
+    pub fn index(&self, row: usize, col: usize) -> usize {
+        row * self.size() + col
+    }
+
+    /// The name of the square at `index`, e.g. `r1c2` for the second square
+    /// of the first row.
+    pub fn square_name(&self, index: usize) -> String {
+        let (row, col) = self.position(index);
+        format!("r{}c{}", row + 1, col + 1)
+    }
+
+    /// Parse a square named as by [`Dimensions::square_name`].
+    pub fn parse_square(&self, name: &str) -> Result<usize> {
+        let invalid = || anyhow!("invalid square {name}, expected r<row>c<column>");
+        let (row, col) = name.strip_prefix('r').and_then(|name| name.split_once('c')).ok_or_else(invalid)?;
+        let (row, col): (usize, usize) = (row.parse().map_err(|_| invalid())?, col.parse().map_err(|_| invalid())?);
+        let size = self.size();
+        if !(1..=size).contains(&row) || !(1..=size).contains(&col) {
+            return Err(anyhow!("square {name} is outside of the {size}x{size} grid"));
+        }
+        Ok(self.index(row - 1, col - 1))
+    }
+
+    /// The digit written as `char` in a puzzle of this size, if any, where
+    /// digits past 9 are written as letters.
+    pub fn parse_digit(&self, char: char) -> Option<u8> {
+        parse_digit_char(char).filter(|&digit| digit > 0 && digit as usize <= self.size())
+    }
+
+    /// For the box which the given `row` and `col` lie within, return a tuple
+    /// of the (row range, column range) which describes that box.
+    pub fn box_ranges(&self, row: usize, col: usize) -> (Range<usize>, Range<usize>) {
+        let top = row / self.box_rows * self.box_rows;
+        let left = col / self.box_cols * self.box_cols;
+        (top..top + self.box_rows, left..left + self.box_cols)
+    }
+
+    /// The (row range, column range) of every box, in reading order.
+    pub fn boxes(&self) -> impl Iterator<Item = (Range<usize>, Range<usize>)> {
+        (0..self.size()).map(|n| {
+            // Each band of rows is size / box_cols = box_rows boxes across.
+            let (box_row, box_col) = n.div_rem(self.box_rows);
+            self.box_ranges(box_row * self.box_rows, box_col * self.box_cols)
+        })
+    }
+
+    /// Returns true if the squares at the given indices share a row, column
+    /// or box.
+    pub fn are_peers(&self, a: usize, b: usize) -> bool {
+        let (arow, acol) = self.position(a);
+        let (brow, bcol) = self.position(b);
+        arow == brow
+            || acol == bcol
+            || (arow / self.box_rows == brow / self.box_rows && acol / self.box_cols == bcol / self.box_cols)
+    }
+}
+
+impl Default for Dimensions {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_boxes() {
+        let dimensions = Dimensions { box_rows: 2, box_cols: 3 };
+        let boxes: Vec<_> = dimensions.boxes().collect();
+        assert_eq!(boxes, [(0..2, 0..3), (0..2, 3..6), (2..4, 0..3), (2..4, 3..6), (4..6, 0..3), (4..6, 3..6)]);
+    }
+
+    #[test]
+    fn test_are_peers() {
+        let dimensions = Dimensions::STANDARD;
+        // Same row, column and box respectively.
+        assert!(dimensions.are_peers(0, 8));
+        assert!(dimensions.are_peers(4, 76));
+        assert!(dimensions.are_peers(30, 50));
+        assert!(!dimensions.are_peers(0, 40));
+        assert!(!dimensions.are_peers(2, 30));
+
+        // Boxes in a 6x6 puzzle are 2 rows tall and 3 columns wide.
+        let dimensions = Dimensions { box_rows: 2, box_cols: 3 };
+        assert!(dimensions.are_peers(0, 8));
+        assert!(!dimensions.are_peers(0, 15));
+    }
+
+    #[test]
+    fn test_square_names() {
+        let dimensions = Dimensions::STANDARD;
+        assert_eq!(dimensions.square_name(10), "r2c2");
+        assert_eq!(dimensions.parse_square("r9c1").unwrap(), 72);
+        assert!(dimensions.parse_square("r0c1").is_err());
+        assert!(dimensions.parse_square("r1c10").is_err());
+        assert!(dimensions.parse_square("c1r1").is_err());
+    }
+
+    #[test]
+    fn test_parse_digit() {
+        assert_eq!(Dimensions::STANDARD.parse_digit('9'), Some(9));
+        assert_eq!(Dimensions::STANDARD.parse_digit('0'), None);
+        assert_eq!(Dimensions::STANDARD.parse_digit('A'), None);
+        assert_eq!(Dimensions::from_size(16).unwrap().parse_digit('g'), Some(16));
+    }
+}
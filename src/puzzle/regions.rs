@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+
+use crate::puzzle::Dimensions;
+use crate::util::DigitChar;
+
+/// Which region each square belongs to, where no region may repeat a digit.
+///
+/// Regions are normally the boxes, but in Jigsaw Sudoku they can be any shape
+/// as long as each has as many squares as a box.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Regions {
+    /// The region of each square, in reading order.
+    ids: Vec<usize>,
+    /// The squares in each region, in reading order.
+    squares: Vec<Vec<usize>>,
+}
+
+impl Regions {
+    /// The regular boxes of a puzzle of the given shape.
+    pub fn boxes(dimensions: Dimensions) -> Self {
+        let mut ids = vec![0; dimensions.squares()];
+        for (id, (rowr, colr)) in dimensions.boxes().enumerate() {
+            for row in rowr {
+                for col in colr.clone() {
+                    ids[dimensions.index(row, col)] = id;
+                }
+            }
+        }
+        Self::from_ids(ids, dimensions.size())
+    }
+
+    /// Parse a region map with a label for each square, in reading order.
+    ///
+    /// Any characters can be used as labels, so long as there are as many
+    /// different ones as there are rows and each labels as many squares.
+    pub fn parse(text: &str, dimensions: Dimensions) -> Result<Self> {
+        let size = dimensions.size();
+        let labels: Vec<char> = text.chars().filter(|char| !char.is_whitespace()).collect();
+        if labels.len() != dimensions.squares() {
+            return Err(anyhow!("region map must have {} squares, not {}", dimensions.squares(), labels.len()));
+        }
+
+        // Regions are numbered in the order their labels first appear.
+        let mut numbers = HashMap::new();
+        let ids: Vec<usize> = labels
+            .iter()
+            .map(|&label| {
+                let next = numbers.len();
+                *numbers.entry(label).or_insert(next)
+            })
+            .collect();
+        if numbers.len() != size {
+            return Err(anyhow!("region map must have {size} regions, not {}", numbers.len()));
+        }
+
+        let regions = Self::from_ids(ids, size);
+        if let Some(id) = regions.squares.iter().position(|squares| squares.len() != size) {
+            let label = labels[regions.squares[id][0]];
+            return Err(anyhow!("region {label} has {} squares rather than {size}", regions.squares[id].len()));
+        }
+        Ok(regions)
+    }
+
+    fn from_ids(ids: Vec<usize>, size: usize) -> Self {
+        let mut squares = vec![Vec::new(); size];
+        for (index, &id) in ids.iter().enumerate() {
+            squares[id].push(index);
+        }
+        Self { ids, squares }
+    }
+
+    /// Render the region map as it is parsed by [`Regions::parse`], labelling
+    /// the regions from 1.
+    pub fn serialize(&self) -> String {
+        // Region ids are always less than the puzzle size, which is at most 25, so this
+        // cast and unwrap are safe.
+        self.ids.iter().map(|&id| (id as u8 + 1).digit_char().unwrap()).collect()
+    }
+
+    /// The region the square at `index` belongs to.
+    pub fn id(&self, index: usize) -> usize {
+        self.ids[index]
+    }
+
+    /// The squares in the region which the square at `index` belongs to.
+    pub fn region_of(&self, index: usize) -> &[usize] {
+        &self.squares[self.ids[index]]
+    }
+
+    /// The squares in each region.
+    pub fn iter(&self) -> impl Iterator<Item = &[usize]> {
+        self.squares.iter().map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SMALL: Dimensions = Dimensions { box_rows: 2, box_cols: 2 };
+
+    #[test]
+    fn test_boxes() {
+        let regions = Regions::boxes(SMALL);
+        assert_eq!(regions.serialize(), "1122112233443344");
+        assert_eq!(regions.region_of(5), [0, 1, 4, 5]);
+        assert_eq!(Regions::parse("1122112233443344", SMALL).unwrap(), regions);
+    }
+
+    #[test]
+    fn test_parse() {
+        let regions = Regions::parse("aaab abbb cccd cddd", SMALL).unwrap();
+        assert_eq!(regions.serialize(), "1112122233343444");
+        assert_eq!(regions.region_of(3), [3, 5, 6, 7]);
+        assert_eq!(regions.id(8), 2);
+
+        // Too few squares, too few regions, and a region with too many squares.
+        assert!(Regions::parse("aaab abbb cccd", SMALL).is_err());
+        assert!(Regions::parse("aaaa aaaa bbbb bbbb", SMALL).is_err());
+        assert!(Regions::parse("aaaa abbb cccd cddd", SMALL).is_err());
+    }
+}
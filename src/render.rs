@@ -0,0 +1,206 @@
+//! Text renderings of puzzles for printing to a terminal or a document.
+
+use crate::layout::{Cell, Layout};
+use crate::puzzle::Puzzle;
+use crate::util::DigitChar;
+
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// How a puzzle is laid out as text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Style {
+    /// A single line with a character for each square, as written by
+    /// [`Puzzle::serialize`].
+    Line,
+    /// A line for each row.
+    Rows,
+    /// A grid split into boxes with ASCII characters, as written by
+    /// [`Puzzle`]'s `Display`.
+    Ascii,
+    /// A grid drawn with Unicode box-drawing characters, like the TUI's.
+    Unicode,
+    /// A Markdown table, with a header row and column numbering the squares.
+    Markdown,
+    /// The initial puzzle and this one side by side, split into boxes.
+    Diff,
+    /// A line such as `r1c2=4` for each square which was filled in.
+    Changes,
+}
+
+/// Renders a puzzle as text, distinguishing the digits that were given from
+/// those that were filled in since.
+pub struct Renderer<'a> {
+    puzzle: &'a Puzzle,
+    /// The puzzle before it was filled in, if it's known.
+    initial: Option<&'a Puzzle>,
+    /// Whether to distinguish the digits with ANSI colors.
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(puzzle: &'a Puzzle) -> Self {
+        Self { puzzle, initial: None, color: false }
+    }
+
+    /// Show the digits which are filled in here as the givens.
+    pub fn with_initial(self, initial: &'a Puzzle) -> Self {
+        Self { initial: Some(initial), ..self }
+    }
+
+    /// Show the givens in bold and filled in digits in color, using ANSI
+    /// escape codes. Markdown always shows the givens in bold instead.
+    pub fn with_color(self, color: bool) -> Self {
+        Self { color, ..self }
+    }
+
+    pub fn render(&self, style: Style) -> String {
+        let size = self.puzzle.dimensions.size();
+        match style {
+            Style::Line => (0..self.puzzle.data.len()).map(|index| self.digit(index, '0')).collect(),
+            Style::Rows => {
+                let mut text = String::new();
+                for index in 0..self.puzzle.data.len() {
+                    text += &self.digit(index, '.');
+                    if (index + 1).is_multiple_of(size) {
+                        text.push('\n');
+                    }
+                }
+                text
+            }
+            Style::Ascii => {
+                // The squares are the only characters in the grid which aren't part of a
+                // line, and they're written in reading order.
+                let mut squares = 0..self.puzzle.data.len();
+                let grid = self.puzzle.to_string();
+                let chars = grid.chars().map(|char| match char {
+                    '-' | '|' | '\n' => char.to_string(),
+                    // The grid has a character for each square, so this unwrap is safe.
+                    _ => self.digit(squares.next().unwrap(), '.'),
+                });
+                chars.collect()
+            }
+            Style::Unicode => {
+                let layout = Layout::new(self.puzzle, 3);
+                let mut text = String::new();
+                for row in layout.rows() {
+                    for cell in row {
+                        match cell {
+                            Cell::Glyph(glyph) => text.push(*glyph),
+                            Cell::Space => text.push(' '),
+                            Cell::Square(index) => text += &self.digit(*index, ' '),
+                        }
+                    }
+                    text.push('\n');
+                }
+                text
+            }
+            Style::Markdown => {
+                let mut text = String::from("|   |");
+                text += &(1..=size).map(|col| format!(" {col} |")).collect::<String>();
+                text += "\n|---|";
+                text += &"---|".repeat(size);
+                for index in 0..self.puzzle.data.len() {
+                    let (row, col) = self.puzzle.dimensions.position(index);
+                    if col == 0 {
+                        text += &format!("\n| {} |", row + 1);
+                    }
+                    let digit = self.puzzle.get(index).and_then(DigitChar::digit_char);
+                    text += &match digit {
+                        Some(char) if self.is_given(index) => format!(" **{char}** |"),
+                        Some(char) => format!(" {char} |"),
+                        None => "   |".to_string(),
+                    };
+                }
+                text.push('\n');
+                text
+            }
+            Style::Diff => {
+                let initial = self.initial.unwrap_or(self.puzzle).to_string();
+                let puzzle = self.render(Style::Ascii);
+                let lines = initial.lines().zip(puzzle.lines());
+                lines.map(|(initial, puzzle)| format!("{initial}   {puzzle}\n")).collect()
+            }
+            Style::Changes => {
+                let filled = (0..self.puzzle.data.len()).filter(|&index| !self.is_given(index));
+                let changes = filled.filter_map(|index| {
+                    let char = self.puzzle.get(index).and_then(DigitChar::digit_char)?;
+                    Some(format!("{}={char}\n", self.puzzle.dimensions.square_name(index)))
+                });
+                changes.collect()
+            }
+        }
+    }
+
+    /// Returns true if the square's digit was given, rather than filled in.
+    fn is_given(&self, index: usize) -> bool {
+        self.initial.is_none_or(|initial| initial.get(index).is_some())
+    }
+
+    /// The square's digit, styled by whether it was given, or `empty` if it
+    /// has none.
+    fn digit(&self, index: usize, empty: char) -> String {
+        let Some(char) = self.puzzle.get(index).and_then(DigitChar::digit_char) else {
+            return empty.to_string();
+        };
+        match (self.color, self.is_given(index)) {
+            (false, _) => char.to_string(),
+            (true, true) => format!("{BOLD}{char}{RESET}"),
+            (true, false) => format!("{CYAN}{char}{RESET}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let initial = Puzzle::from_str("1.3.....2..3...1").unwrap();
+        let mut puzzle = initial.clone();
+        puzzle.set(1, Some(4));
+        let renderer = Renderer::new(&puzzle).with_initial(&initial);
+
+        assert_eq!(renderer.render(Style::Line), "1430000020030001");
+        assert_eq!(renderer.render(Style::Rows), "143.\n....\n2..3\n...1\n");
+        assert_eq!(renderer.render(Style::Ascii), puzzle.to_string());
+        assert_eq!(
+            renderer.render(Style::Unicode).lines().take(3).collect::<Vec<_>>(),
+            ["┏━━━┯━━━┳━━━┯━━━┓", "┃ 1 │ 4 ┃ 3 │   ┃", "┠───┼───╂───┼───┨"]
+        );
+        assert_eq!(
+            renderer.render(Style::Markdown).lines().take(3).collect::<Vec<_>>(),
+            ["|   | 1 | 2 | 3 | 4 |", "|---|---|---|---|---|", "| 1 | **1** | 4 | **3** |   |"]
+        );
+        assert_eq!(
+            renderer.render(Style::Diff).lines().take(2).collect::<Vec<_>>(),
+            ["-------   -------", "|1.|3.|   |14|3.|"]
+        );
+        assert_eq!(renderer.render(Style::Changes), "r1c2=4\n");
+        assert_eq!(Renderer::new(&puzzle).render(Style::Changes), "");
+    }
+
+    #[test]
+    fn test_color() {
+        let initial = Puzzle::from_str("1.3.....2..3...1").unwrap();
+        let mut puzzle = initial.clone();
+        puzzle.set(1, Some(4));
+
+        let renderer = Renderer::new(&puzzle).with_initial(&initial).with_color(true);
+        assert!(renderer.render(Style::Rows).starts_with("\x1b[1m1\x1b[0m\x1b[36m4\x1b[0m\x1b[1m3\x1b[0m.\n"));
+        assert!(renderer.render(Style::Ascii).starts_with("-------\n|\x1b[1m1\x1b[0m\x1b[36m4\x1b[0m|"));
+        // Markdown is meant to be rendered by something else, so it never has escape codes.
+        assert!(!renderer.render(Style::Markdown).contains('\x1b'));
+
+        let diff = renderer.render(Style::Diff);
+        assert!(diff.lines().nth(1).unwrap().starts_with("|1.|3.|   |\x1b[1m1\x1b[0m\x1b[36m4\x1b[0m|"));
+
+        // Without the initial puzzle, every digit counts as given.
+        let renderer = Renderer::new(&puzzle).with_color(true);
+        assert!(!renderer.render(Style::Line).contains(CYAN));
+    }
+}
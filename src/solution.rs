@@ -3,16 +3,26 @@ use anyhow::Result;
 use crate::solution::base::BaseSolution;
 
 pub mod base;
+pub(crate) mod occupancy;
+pub mod progress;
+pub mod trace;
+#[cfg(feature = "cli")]
 pub mod tui;
 
 /// Implementors of [`Solution`] must either encapsulate a [`BaseSolution`] or
 /// be one itself.
 ///
 /// This trait gives those higher level types the ability to hook into `set`
-/// calls in order to perform different functions, such as render to a TUI (as
-/// [`TuiSolution`] does).
+/// calls in order to perform different functions, such as record a trace (as
+/// [`TraceSolution`](trace::TraceSolution) does).
 pub trait Solution {
     fn set(&mut self, index: usize, value: Option<u8>) -> Result<()>;
 
+    /// Like [`Solution::set`], but with a short explanation of why the edit is
+    /// being made, for implementors which keep a record of edits.
+    fn set_with_reason(&mut self, index: usize, value: Option<u8>, _reason: &'static str) -> Result<()> {
+        self.set(index, value)
+    }
+
     fn base(&mut self) -> &mut BaseSolution;
 }
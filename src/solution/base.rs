@@ -1,14 +1,14 @@
-use std::collections::HashSet;
-use std::ops::Range;
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 
-use crate::PUZZLE_DIGITS;
-#[cfg(debug_assertions)]
+use crate::algorithms::{Limit, Limits};
+use crate::cancel::CancellationToken;
 use crate::metrics::Metrics;
 use crate::puzzle::Puzzle;
 use crate::solution::Solution;
-use crate::util::DivRem;
+use crate::solution::occupancy::Occupancy;
+use crate::solution::progress::{Observer, Progress};
 
 /// The base solution that is used by other [`Solution`] implementors.
 ///
@@ -16,86 +16,149 @@ use crate::util::DivRem;
 /// which is trivial. It is used when no special behavior is needed when finding
 /// a solution to a puzzle.
 pub struct BaseSolution {
-    pub puzzle: Puzzle,
-    #[cfg(debug_assertions)]
+    /// The puzzle being solved, which is only edited through
+    /// [`BaseSolution::set`] so that the occupancy stays up to date.
+    puzzle: Puzzle,
+    /// The puzzle as it was before solving began.
+    pub initial: Puzzle,
+    /// When the solver must give up by, see [`BaseSolution::with_limits`].
+    deadline: Option<Instant>,
+    max_steps: Option<u64>,
+    observer: Option<Observer>,
+    /// Which digits each row, column and region holds, for checking the
+    /// standard rules without scanning the puzzle.
+    occupancy: Occupancy,
     pub metrics: Metrics,
 }
 
 impl BaseSolution {
     pub fn new(puzzle: Puzzle) -> Self {
-        Self { puzzle, metrics: Metrics::default() }
+        Self {
+            metrics: Metrics::default(),
+            occupancy: Occupancy::new(&puzzle),
+            initial: puzzle.clone(),
+            puzzle,
+            deadline: None,
+            max_steps: None,
+            observer: None,
+        }
+    }
+
+    /// Make solvers give up once they reach one of the `limits`. The timeout
+    /// counts from when this is called.
+    pub fn with_limits(self, limits: Limits) -> Self {
+        Self { deadline: limits.timeout.map(|timeout| Instant::now() + timeout), max_steps: limits.max_steps, ..self }
+    }
+
+    /// Call `callback` with the solve's [`Progress`] every `interval` or so,
+    /// for as long as it runs. This is much less often than each edit, so it
+    /// is cheap enough for showing progress to the user or recording
+    /// telemetry.
+    pub fn with_observer(self, interval: Duration, callback: impl FnMut(&Progress) + Send + 'static) -> Self {
+        Self { observer: Some(Observer::new(interval, callback)), ..self }
+    }
+
+    /// Let the observer, and the progress callback of `cancel`, know how far
+    /// the solve has got, if they're due to be told. Solvers should call this
+    /// regularly.
+    pub fn report_progress(&mut self, depth: usize, cancel: Option<&CancellationToken>) {
+        let steps = self.steps();
+        if let Some(observer) = &mut self.observer {
+            observer.observe(steps, depth);
+        }
+        if let Some(cancel) = cancel {
+            cancel.report_progress(steps, depth);
+        }
+    }
+
+    /// The limit the solver has reached, if any, in which case it should give
+    /// up.
+    pub fn limit_reached(&self) -> Option<Limit> {
+        if self.max_steps.is_some_and(|max_steps| self.steps() >= max_steps) {
+            Some(Limit::MaxSteps)
+        } else if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(Limit::Timeout)
+        } else {
+            None
+        }
+    }
+
+    /// How many edits have been made to the puzzle.
+    pub fn steps(&self) -> u64 {
+        self.metrics.total_edits()
+    }
+
+    /// The puzzle being solved, as far as the solver has got.
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn into_puzzle(self) -> Puzzle {
+        self.puzzle
     }
 
     pub fn get(&mut self, idx: usize) -> Option<u8> {
-        #[cfg(debug_assertions)]
-        self.metrics.record_view(idx);
+        self.metrics.record_view();
         self.puzzle.get(idx)
     }
 
     pub fn set(&mut self, idx: usize, value: Option<u8>) {
-        #[cfg(debug_assertions)]
-        self.metrics.record_edit(idx);
+        self.metrics.record_edit();
         self.puzzle.set(idx, value);
+        // The puzzle ignores edits to its initial squares, if it's tracking them.
+        self.occupancy.set(idx, self.puzzle.get(idx));
     }
 
     pub fn iter_puzzle(&mut self) -> impl Iterator<Item = (usize, &Option<u8>)> {
-        self.puzzle.data.iter().enumerate().inspect(|(idx, _)| {
-            #[cfg(debug_assertions)]
-            self.metrics.record_view(*idx);
-        })
+        self.puzzle.data.iter().enumerate().inspect(|_| self.metrics.record_view())
+    }
+
+    /// Returns an error naming the squares which already break the puzzle's
+    /// rules, if any. Solvers must check this before they start, since
+    /// [`BaseSolution::is_valid_digit`] only compares the digit in a square
+    /// with its peers, assuming that they follow the rules between
+    /// themselves.
+    pub fn check_puzzle(&self) -> Result<()> {
+        let Err(invalid_squares) = self.puzzle.validate() else {
+            return Ok(());
+        };
+        let mut invalid_squares: Vec<_> = invalid_squares.into_iter().collect();
+        invalid_squares.sort_unstable();
+        let names: Vec<_> = invalid_squares.iter().map(|&index| self.puzzle.dimensions.square_name(index)).collect();
+        Err(anyhow!("the puzzle already breaks its rules at {}", names.join(", ")))
     }
 
+    /// Returns true if the digit in the square at `idx` follows every one of
+    /// the puzzle's constraints, or if the square is empty.
     pub fn is_valid_digit(&mut self, idx: usize) -> bool {
-        if idx >= PUZZLE_DIGITS {
+        if idx >= self.puzzle.data.len() {
             return false;
         }
-        let (row, col) = idx.div_rem(9);
-        let (rowr, colr) = box_ranges(row, col);
-
-        // Per the rules of Sudoku, every square must have a unique value among its row,
-        // column, and box.
-        self.is_valid_row(row) && self.is_valid_col(col) && self.is_valid_box(rowr, colr)
-    }
-
-    fn is_valid_row(&mut self, row: usize) -> bool {
-        let mut seen = HashSet::new();
-        for col in 0..9 {
-            let idx = row * 9 + col;
-            if let Some(digit) = self.get(idx) {
-                if !seen.insert(digit) {
-                    return false;
-                }
-            }
-        }
-        true
-    }
-
-    fn is_valid_col(&mut self, col: usize) -> bool {
-        let mut seen = HashSet::new();
-        for row in 0..9 {
-            let idx = row * 9 + col;
-            if let Some(digit) = self.get(idx) {
-                if !seen.insert(digit) {
-                    return false;
-                }
-            }
-        }
-        true
-    }
-
-    fn is_valid_box(&mut self, rowr: Range<usize>, colr: Range<usize>) -> bool {
-        let mut seen = HashSet::new();
-        for row in rowr {
-            for col in colr.clone() {
-                let idx = row * 9 + col;
-                if let Some(digit) = self.get(idx) {
-                    if !seen.insert(digit) {
-                        return false;
-                    }
-                }
-            }
-        }
-        true
+        let Some(digit) = self.get(idx) else {
+            return true;
+        };
+        self.record_peer_views(idx);
+        self.occupancy.is_unique(idx)
+            && self.puzzle.extra_constraints().all(|constraint| constraint.allows(&self.puzzle, idx, digit))
+    }
+
+    /// Return the digits which could go in the square at `idx` without
+    /// breaking any of the puzzle's constraints.
+    pub fn candidates(&mut self, idx: usize) -> Vec<u8> {
+        self.record_peer_views(idx);
+        let puzzle = &self.puzzle;
+        self.occupancy
+            .candidates(idx, puzzle.dimensions.size())
+            .filter(|&digit| puzzle.extra_constraints().all(|constraint| constraint.allows(puzzle, idx, digit)))
+            .collect()
+    }
+
+    /// The constraints besides the standard ones read the puzzle directly, so
+    /// count a view of each square each of them compares with the square at
+    /// `idx`.
+    fn record_peer_views(&mut self, idx: usize) {
+        let views = self.puzzle.extra_constraints().map(|constraint| constraint.peer_count(&self.puzzle, idx)).sum();
+        self.metrics.record_views(views);
     }
 }
 
@@ -109,29 +172,3 @@ impl Solution for BaseSolution {
         self
     }
 }
-
-/// For the box which the given `row` and `col` lie within, return a tuple of
-/// the (row range, column range) which describes that box.
-fn box_ranges(row: usize, col: usize) -> (Range<usize>, Range<usize>) {
-    match row {
-        0..3 => match col {
-            0..3 => (0..3, 0..3),
-            3..6 => (0..3, 3..6),
-            6..9 => (0..3, 6..9),
-            _ => unreachable!(),
-        },
-        3..6 => match col {
-            0..3 => (3..6, 0..3),
-            3..6 => (3..6, 3..6),
-            6..9 => (3..6, 6..9),
-            _ => unreachable!(),
-        },
-        6..9 => match col {
-            0..3 => (6..9, 0..3),
-            3..6 => (6..9, 3..6),
-            6..9 => (6..9, 6..9),
-            _ => unreachable!(),
-        },
-        _ => unreachable!(),
-    }
-}
@@ -0,0 +1,138 @@
+use crate::puzzle::Puzzle;
+
+/// Which digits each row, column and region of a puzzle holds, as bitmasks
+/// which are kept up to date on every edit so that checking a digit against
+/// the [`Standard`](crate::puzzle::Standard) rules takes constant time, rather
+/// than a scan over the square's peers.
+#[derive(Clone, Debug)]
+pub struct Occupancy {
+    /// The digit in each square, so that edits know what they're replacing.
+    digits: Vec<Option<u8>>,
+    /// The units each square belongs to, which are numbered with the rows
+    /// first, then the columns, then the regions.
+    units: Vec<[usize; 3]>,
+    occupants: Vec<Occupants>,
+}
+
+/// The digits held by a row, column or region.
+#[derive(Clone, Copy, Debug, Default)]
+struct Occupants {
+    /// A bit for each digit which the unit holds at least once.
+    mask: u32,
+    /// How many times the unit holds each digit, which go up to 25, since a
+    /// digit may briefly be repeated while solving and clearing one of them
+    /// mustn't clear its bit.
+    counts: [u8; 26],
+}
+
+impl Occupancy {
+    pub fn new(puzzle: &Puzzle) -> Self {
+        let dimensions = puzzle.dimensions;
+        let size = dimensions.size();
+        let units = (0..puzzle.data.len())
+            .map(|index| {
+                let (row, col) = dimensions.position(index);
+                [row, size + col, 2 * size + puzzle.regions.id(index)]
+            })
+            .collect();
+        let unit_count = 2 * size + puzzle.regions.iter().count();
+        let mut occupancy =
+            Self { digits: vec![None; puzzle.data.len()], units, occupants: vec![Occupants::default(); unit_count] };
+        for (index, &digit) in puzzle.data.iter().enumerate() {
+            occupancy.set(index, digit);
+        }
+        occupancy
+    }
+
+    /// Put `value` in the square at `index`, replacing whatever it held.
+    pub fn set(&mut self, index: usize, value: Option<u8>) {
+        let previous = std::mem::replace(&mut self.digits[index], value);
+        for unit in self.units[index] {
+            let occupants = &mut self.occupants[unit];
+            if let Some(digit) = previous {
+                let count = &mut occupants.counts[digit as usize];
+                *count -= 1;
+                if *count == 0 {
+                    occupants.mask &= !(1 << digit);
+                }
+            }
+            if let Some(digit) = value {
+                occupants.counts[digit as usize] += 1;
+                occupants.mask |= 1 << digit;
+            }
+        }
+    }
+
+    /// Returns true if the square at `index` is empty, or no other square in
+    /// its row, column or region holds its digit. This is the same as
+    /// [`Occupancy::allows`] with the square's own digit, but quicker.
+    pub fn is_unique(&self, index: usize) -> bool {
+        let Some(digit) = self.digits[index] else {
+            return true;
+        };
+        self.units[index].iter().all(|&unit| self.occupants[unit].counts[digit as usize] == 1)
+    }
+
+    /// Returns true if no other square in the row, column or region of the
+    /// square at `index` holds `digit`.
+    #[cfg(test)]
+    pub fn allows(&self, index: usize, digit: u8) -> bool {
+        self.taken(index) & (1 << digit) == 0
+    }
+
+    /// The digits from 1 to `size` which no other square in the row, column
+    /// or region of the square at `index` holds.
+    pub fn candidates(&self, index: usize, size: usize) -> impl Iterator<Item = u8> {
+        let taken = self.taken(index);
+        // This cast to u8 is safe since puzzles are never more than 25 squares across.
+        (1..=size as u8).filter(move |&digit| taken & (1 << digit) == 0)
+    }
+
+    /// A bit for each digit held by a square which shares a unit with the
+    /// square at `index`, not counting the square itself.
+    fn taken(&self, index: usize) -> u32 {
+        let own = self.digits[index];
+        self.units[index].iter().fold(0, |taken, &unit| {
+            let occupants = &self.occupants[unit];
+            let mut mask = occupants.mask;
+            if let Some(digit) = own
+                && occupants.counts[digit as usize] == 1
+            {
+                mask &= !(1 << digit);
+            }
+            taken | mask
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::puzzle::Standard;
+    use crate::puzzle::constraint::Constraint;
+
+    #[test]
+    fn test_matches_standard() {
+        let mut puzzle = Puzzle::from_str("1.3.....2..3...1\nregions aaab abbb cccd cddd").unwrap();
+        let mut occupancy = Occupancy::new(&puzzle);
+        // Repeat a digit in the first row, then clear one of them again.
+        for (index, value) in [(1, Some(1)), (5, Some(4)), (1, None), (5, None), (6, Some(2))] {
+            puzzle.set(index, value);
+            occupancy.set(index, value);
+            for index in 0..puzzle.data.len() {
+                let digit = puzzle.get(index);
+                assert_eq!(occupancy.is_unique(index), digit.is_none_or(|digit| occupancy.allows(index, digit)));
+                for digit in 1..=4 {
+                    assert_eq!(
+                        occupancy.allows(index, digit),
+                        Standard.allows(&puzzle, index, digit),
+                        "{index} {digit}"
+                    );
+                }
+            }
+        }
+        assert_eq!(occupancy.candidates(1, 4).collect::<Vec<_>>(), [2, 4]);
+    }
+}
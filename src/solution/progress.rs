@@ -0,0 +1,59 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A snapshot of how far along a solve is, given to the callback set with
+/// [`BaseSolution::with_observer`](super::base::BaseSolution::with_observer).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Progress {
+    /// How many edits have been made to the puzzle.
+    pub steps: u64,
+    /// How deep the solver's search is, which means something different to
+    /// each algorithm, e.g. how many squares are filled in for
+    /// [`Backtracking`](crate::Backtracking).
+    pub depth: usize,
+    /// How long the solve has been running for.
+    pub elapsed: Duration,
+}
+
+/// Calls back with the solve's [`Progress`] at most once per interval.
+pub(crate) struct Observer {
+    callback: Box<dyn FnMut(&Progress) + Send>,
+    interval: Duration,
+    started: Instant,
+    /// When the callback is next due.
+    next: Instant,
+    /// The steps at which the clock was last checked.
+    checked_at: u64,
+}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observer").field("interval", &self.interval).finish_non_exhaustive()
+    }
+}
+
+impl Observer {
+    /// How many steps to take between checking the clock, since solvers
+    /// report their progress on nearly every step and reading the clock costs
+    /// as much as several of them.
+    const CHECK_EVERY: u64 = 1024;
+
+    pub(crate) fn new(interval: Duration, callback: impl FnMut(&Progress) + Send + 'static) -> Self {
+        let started = Instant::now();
+        Self { callback: Box::new(callback), interval, started, next: started + interval, checked_at: 0 }
+    }
+
+    /// Call back if the interval has passed since the last time, checking
+    /// only once every [`Observer::CHECK_EVERY`] steps.
+    pub(crate) fn observe(&mut self, steps: u64, depth: usize) {
+        if steps < self.checked_at + Self::CHECK_EVERY {
+            return;
+        }
+        self.checked_at = steps;
+        let now = Instant::now();
+        if now >= self.next {
+            (self.callback)(&Progress { steps, depth, elapsed: now - self.started });
+            self.next = now + self.interval;
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::solution::Solution;
+use crate::solution::base::BaseSolution;
+use crate::trace::TraceWriter;
+
+/// Writes every edit to a trace file before passing it on to another
+/// [`Solution`].
+///
+/// The trace can be replayed later, see [`crate::trace::parse`].
+pub struct TraceSolution<S: Solution> {
+    inner: S,
+    writer: TraceWriter<BufWriter<File>>,
+}
+
+impl<S: Solution> TraceSolution<S> {
+    pub fn create(mut inner: S, path: &Path) -> Result<Self> {
+        let writer = TraceWriter::new(BufWriter::new(File::create(path)?), inner.base().puzzle())?;
+        Ok(Self { inner, writer })
+    }
+
+    /// Flush the trace file and return the wrapped solution.
+    pub fn finish(self) -> Result<S> {
+        self.writer.into_inner()?;
+        Ok(self.inner)
+    }
+}
+
+impl<S: Solution> Solution for TraceSolution<S> {
+    fn set(&mut self, index: usize, value: Option<u8>) -> Result<()> {
+        self.writer.write(index, value, None)?;
+        self.inner.set(index, value)
+    }
+
+    fn set_with_reason(&mut self, index: usize, value: Option<u8>, reason: &'static str) -> Result<()> {
+        self.writer.write(index, value, Some(reason))?;
+        self.inner.set_with_reason(index, value, reason)
+    }
+
+    fn base(&mut self) -> &mut BaseSolution {
+        self.inner.base()
+    }
+}
@@ -1,46 +1,67 @@
-use std::sync::mpsc::SyncSender;
-use std::thread;
-use std::time::Duration;
-
 use anyhow::Result;
 
+use crate::algorithms::Limits;
 use crate::puzzle::Puzzle;
 use crate::solution::Solution;
 use crate::solution::base::BaseSolution;
-use crate::tui::Tui;
-
-const DEFAULT_DELAY_MS: u64 = 50;
+use crate::trace::{Edit, SharedTrace};
 
-/// Renders the solution to a TUI as it is being found.
+/// Records the solution as it is being found, so that an
+/// [`Animation`](crate::tui::animation::Animation) can render it to a TUI.
 ///
-/// This is great for visualizing how an [`Algorithm`] works, and how different
+/// This is great for visualizing how an [`Algorithm`](crate::Algorithm) works, and how different
 /// ones compare to each other.
+///
+/// The trace is marked as finished when this is dropped, even if the solver
+/// panicked. Every edit is kept so that the animation can be rewound, so the
+/// solver gives up after making a limited number of them.
 pub struct TuiSolution {
-    tui: Tui,
     base: BaseSolution,
-    delay: Duration,
+    trace: SharedTrace,
 }
 
 impl TuiSolution {
-    pub fn init(puzzle: Puzzle, kill_channel: SyncSender<()>, delay_ms: Option<u64>) -> Self {
-        let delay = Duration::from_millis(delay_ms.unwrap_or(DEFAULT_DELAY_MS));
-        Self { tui: Tui::init(kill_channel), base: BaseSolution::new(puzzle), delay }
+    /// Enough to animate for hours at the fastest speed, while using a few
+    /// dozen megabytes.
+    pub const MAX_EDITS: u64 = 1_000_000;
+
+    /// Record the solve of `puzzle` to `trace`, giving up after `max_steps`
+    /// edits, or [`TuiSolution::MAX_EDITS`] if not given.
+    pub fn new(puzzle: Puzzle, trace: SharedTrace, max_steps: Option<u64>) -> Self {
+        let limits = Limits { max_steps: Some(max_steps.unwrap_or(Self::MAX_EDITS)), ..Limits::default() };
+        Self { base: BaseSolution::new(puzzle).with_limits(limits), trace }
     }
 
     pub fn into_base(self) -> BaseSolution {
-        self.base
+        // We can't move out of a type which implements Drop, so swap in an empty puzzle.
+        let mut solution = self;
+        std::mem::replace(&mut solution.base, BaseSolution::new(Puzzle::default()))
+    }
+
+    fn record(&mut self, edit: Edit) -> Result<()> {
+        self.base.set(edit.index, edit.value);
+        self.trace.push(edit);
+        Ok(())
     }
 }
 
 impl Solution for TuiSolution {
     fn set(&mut self, index: usize, value: Option<u8>) -> Result<()> {
-        self.base.set(index, value);
-        self.tui.render(&mut self.base.puzzle)?;
-        thread::sleep(self.delay);
-        Ok(())
+        self.record(Edit { index, previous: self.base.puzzle().get(index), value, reason: None })
+    }
+
+    fn set_with_reason(&mut self, index: usize, value: Option<u8>, reason: &'static str) -> Result<()> {
+        let previous = self.base.puzzle().get(index);
+        self.record(Edit { index, previous, value, reason: Some(reason.into()) })
     }
 
     fn base(&mut self) -> &mut BaseSolution {
         &mut self.base
     }
 }
+
+impl Drop for TuiSolution {
+    fn drop(&mut self) {
+        self.trace.finish();
+    }
+}
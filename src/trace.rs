@@ -0,0 +1,456 @@
+use std::borrow::Cow;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::puzzle::Puzzle;
+
+const HEADER: &str = "# sudoku trace";
+
+/// A single edit made to a puzzle while it was being solved.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Edit {
+    pub index: usize,
+    /// The value of the square before the edit.
+    pub previous: Option<u8>,
+    /// The value of the square after the edit.
+    pub value: Option<u8>,
+    /// Why the solver made this edit, if it said.
+    pub reason: Option<Cow<'static, str>>,
+}
+
+impl Edit {
+    pub fn apply(&self, puzzle: &mut Puzzle) {
+        puzzle.set(self.index, self.value);
+    }
+
+    pub fn revert(&self, puzzle: &mut Puzzle) {
+        puzzle.set(self.index, self.previous);
+    }
+}
+
+/// A trace which can be appended to by a solver on one thread while being
+/// read on another.
+///
+/// A solver can make edits far faster than anyone can watch them, so a trace
+/// made with [`SharedTrace::bounded`] makes the solver wait whenever it gets
+/// too far ahead of the reader.
+#[derive(Clone, Default)]
+pub struct SharedTrace(Arc<Shared>);
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<TraceState>,
+    /// Notified when the solver may add more edits.
+    space: Condvar,
+}
+
+pub struct TraceState {
+    pub edits: Vec<Edit>,
+    /// Set once no more edits will be added.
+    pub done: bool,
+    /// How many edits the trace may hold before the solver has to wait.
+    limit: usize,
+    /// How far the solver may get ahead of the reader, if it has to wait at
+    /// all.
+    ahead: Option<usize>,
+}
+
+impl Default for TraceState {
+    fn default() -> Self {
+        Self { edits: Vec::new(), done: false, limit: usize::MAX, ahead: None }
+    }
+}
+
+impl SharedTrace {
+    /// A trace which already holds all of its edits.
+    pub fn finished(edits: Vec<Edit>) -> Self {
+        let state = TraceState { edits, done: true, ..TraceState::default() };
+        Self(Arc::new(Shared { state: Mutex::new(state), space: Condvar::new() }))
+    }
+
+    /// A trace which the solver can only get `ahead` edits further into than
+    /// the reader has [read](SharedTrace::read_to).
+    pub fn bounded(ahead: usize) -> Self {
+        let state = TraceState { limit: ahead, ahead: Some(ahead), ..TraceState::default() };
+        Self(Arc::new(Shared { state: Mutex::new(state), space: Condvar::new() }))
+    }
+
+    /// Add an edit, first waiting for the reader to catch up if the solver is
+    /// too far ahead.
+    pub fn push(&self, edit: Edit) {
+        let mut state = self.lock();
+        while state.edits.len() >= state.limit {
+            state = self.0.space.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        state.edits.push(edit);
+    }
+
+    /// Let the solver know that the reader has got to `position`, so it can
+    /// carry on if it was waiting.
+    pub fn read_to(&self, position: usize) {
+        let mut state = self.lock();
+        if let Some(ahead) = state.ahead {
+            state.limit = state.limit.max(position.saturating_add(ahead));
+            self.0.space.notify_all();
+        }
+    }
+
+    /// Stop making the solver wait for the reader, such as when nothing is
+    /// going to read any more of the trace.
+    pub fn release(&self) {
+        let mut state = self.lock();
+        state.ahead = None;
+        state.limit = usize::MAX;
+        self.0.space.notify_all();
+    }
+
+    pub fn finish(&self) {
+        self.lock().done = true;
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, TraceState> {
+        // A panic while holding the lock can't leave the trace in an inconsistent state,
+        // so it is fine to ignore poisoning.
+        self.0.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Writes a trace in a line-based text format as edits are made.
+///
+/// The format is a header, the starting puzzle and any of its other rules
+/// such as Killer Sudoku cages, then one line per edit:
+///
+/// ```text
+/// # sudoku trace
+/// puzzle 050703060007000800000816000000030000005000100730040086906000204840572093000409000
+/// 1 set 0 1 try
+/// 2 set 0 2 try
+/// 3 clear 0 backtrack
+/// ```
+///
+/// Each edit line holds the step number, `set <index> <value>` or `clear
+/// <index>`, and optionally the reason for the edit.
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    step: usize,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(mut writer: W, puzzle: &Puzzle) -> Result<Self> {
+        writeln!(writer, "{HEADER}")?;
+        writeln!(writer, "puzzle {}", puzzle.serialize())?;
+        for rule in puzzle.serialize_rules() {
+            writeln!(writer, "{rule}")?;
+        }
+        Ok(Self { writer, step: 0 })
+    }
+
+    pub fn write(&mut self, index: usize, value: Option<u8>, reason: Option<&str>) -> Result<()> {
+        self.step += 1;
+        match value {
+            Some(value) => write!(self.writer, "{} set {index} {value}", self.step)?,
+            None => write!(self.writer, "{} clear {index}", self.step)?,
+        }
+        if let Some(reason) = reason {
+            write!(self.writer, " {reason}")?;
+        }
+        writeln!(self.writer)?;
+        Ok(())
+    }
+
+    pub fn into_inner(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Parse a trace written by [`TraceWriter`] into the starting puzzle and its
+/// edits.
+pub fn parse(input: &str) -> Result<(Puzzle, Vec<Edit>)> {
+    let mut lines =
+        input.lines().enumerate().map(|(idx, line)| (idx + 1, line.trim())).filter(|(_, line)| !line.is_empty());
+
+    match lines.next() {
+        Some((_, HEADER)) => {}
+        _ => return Err(anyhow!("missing \"{HEADER}\" header")),
+    }
+
+    let mut puzzle = match lines.next() {
+        Some((number, line)) => {
+            let text = line.strip_prefix("puzzle ").ok_or_else(|| anyhow!("line {number}: expected puzzle"))?;
+            Puzzle::from_str(text).with_context(|| format!("line {number}"))?
+        }
+        None => return Err(anyhow!("missing puzzle")),
+    };
+    let mut lines = lines.peekable();
+    // Edit lines always start with their step number.
+    while let Some((number, line)) = lines.next_if(|(_, line)| !line.starts_with(|char: char| char.is_ascii_digit())) {
+        puzzle.parse_rule(line).with_context(|| format!("line {number}"))?;
+    }
+
+    let mut state = puzzle.clone();
+    let mut edits = Vec::new();
+    for (number, line) in lines {
+        let edit = parse_edit(line, &state).with_context(|| format!("line {number}"))?;
+        if edit.index >= state.data.len() {
+            return Err(anyhow!("line {number}: index {} is out of bounds", edit.index));
+        }
+        edit.apply(&mut state);
+        edits.push(edit);
+    }
+
+    Ok((puzzle, edits))
+}
+
+fn parse_edit(line: &str, state: &Puzzle) -> Result<Edit> {
+    let mut parts = line.splitn(3, ' ');
+    // The step number is only there for readability; edits are always in order.
+    parts.next().unwrap_or_default().parse::<usize>().context("invalid step")?;
+    let kind = parts.next().ok_or_else(|| anyhow!("missing edit kind"))?;
+    let rest = parts.next().ok_or_else(|| anyhow!("missing index"))?;
+
+    let (index, value, reason) = match kind {
+        "set" => {
+            let mut parts = rest.splitn(3, ' ');
+            let index = parts.next().unwrap_or_default();
+            let value = parts.next().ok_or_else(|| anyhow!("missing value"))?.parse::<u8>().context("invalid value")?;
+            let size = state.dimensions.size();
+            if !(1..=size).contains(&(value as usize)) {
+                return Err(anyhow!("value {value} is not a digit from 1-{size}"));
+            }
+            (index, Some(value), parts.next())
+        }
+        "clear" => {
+            let mut parts = rest.splitn(2, ' ');
+            (parts.next().unwrap_or_default(), None, parts.next())
+        }
+        _ => return Err(anyhow!("unknown edit kind {kind}")),
+    };
+
+    let index = index.parse::<usize>().context("invalid index")?;
+    let previous = state.data.get(index).copied().flatten();
+    let reason = reason.map(|reason| Cow::Owned(reason.to_string()));
+    Ok(Edit { index, previous, value, reason })
+}
+
+/// A puzzle that can be moved to the state it was in after any number of the
+/// edits in a trace.
+pub struct Playback {
+    puzzle: Puzzle,
+    /// The number of edits from the trace which have been applied to `puzzle`.
+    position: usize,
+    /// The number of squares filled in the starting puzzle.
+    initially_filled: usize,
+    /// The number of applied edits which cleared a square.
+    backtracks: usize,
+}
+
+impl Playback {
+    /// Start playback from `puzzle`, which must be the state before any edits.
+    pub fn new(puzzle: Puzzle) -> Self {
+        let initially_filled = filled_count(&puzzle);
+        Self { puzzle, position: 0, initially_filled, backtracks: 0 }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of squares filled in beyond those in the starting puzzle.
+    pub fn depth(&self) -> usize {
+        filled_count(&self.puzzle).saturating_sub(self.initially_filled)
+    }
+
+    pub fn backtracks(&self) -> usize {
+        self.backtracks
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    /// The last edit applied, if any.
+    pub fn last_edit<'a>(&self, trace: &'a [Edit]) -> Option<&'a Edit> {
+        trace.get(self.position.checked_sub(1)?)
+    }
+
+    /// Apply or revert edits from `trace` until the first `position` of them
+    /// are applied.
+    ///
+    /// `position` is clamped to the length of the trace.
+    pub fn seek(&mut self, trace: &[Edit], position: usize) {
+        let position = position.min(trace.len());
+        while self.position < position {
+            let edit = &trace[self.position];
+            edit.apply(&mut self.puzzle);
+            if edit.value.is_none() {
+                self.backtracks += 1;
+            }
+            self.position += 1;
+        }
+        while self.position > position {
+            self.position -= 1;
+            let edit = &trace[self.position];
+            edit.revert(&mut self.puzzle);
+            if edit.value.is_none() {
+                self.backtracks -= 1;
+            }
+        }
+    }
+}
+
+fn filled_count(puzzle: &Puzzle) -> usize {
+    puzzle.data.iter().filter(|square| square.is_some()).count()
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use std::thread;
+    use std::time::Duration;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::puzzle::{Cage, Variant};
+
+    #[test]
+    fn test_seek() {
+        let initial = Puzzle::from_str(&"0".repeat(81)).unwrap();
+        let trace = [
+            Edit { index: 0, previous: None, value: Some(1), reason: None },
+            Edit { index: 0, previous: Some(1), value: Some(2), reason: None },
+            Edit { index: 1, previous: None, value: Some(3), reason: None },
+            Edit { index: 1, previous: Some(3), value: None, reason: None },
+        ];
+
+        let mut playback = Playback::new(initial.clone());
+        playback.seek(&trace, 3);
+        assert_eq!(playback.puzzle.get(0), Some(2));
+        assert_eq!(playback.puzzle.get(1), Some(3));
+
+        assert_eq!(playback.depth(), 2);
+        assert_eq!(playback.backtracks(), 0);
+
+        playback.seek(&trace, 4);
+        assert_eq!(playback.depth(), 1);
+        assert_eq!(playback.backtracks(), 1);
+
+        playback.seek(&trace, 1);
+        assert_eq!(playback.puzzle.get(0), Some(1));
+        assert_eq!(playback.puzzle.get(1), None);
+        assert_eq!(playback.backtracks(), 0);
+
+        playback.seek(&trace, 100);
+        assert_eq!(playback.position(), trace.len());
+
+        playback.seek(&trace, 0);
+        assert_eq!(playback.puzzle, initial);
+    }
+
+    #[test]
+    fn test_bounded() {
+        let trace = SharedTrace::bounded(2);
+        let edit = Edit { index: 0, previous: None, value: Some(1), reason: None };
+        let solver = {
+            let trace = trace.clone();
+            let edit = edit.clone();
+            thread::spawn(move || {
+                for _ in 0..5 {
+                    trace.push(edit.clone());
+                }
+            })
+        };
+
+        // The solver can't get more than two edits ahead of the reader.
+        let wait_for = |len| {
+            while trace.lock().edits.len() < len {
+                thread::yield_now();
+            }
+        };
+        wait_for(2);
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(trace.lock().edits.len(), 2);
+
+        trace.read_to(1);
+        wait_for(3);
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(trace.lock().edits.len(), 3);
+
+        trace.release();
+        solver.join().unwrap();
+        assert_eq!(trace.lock().edits.len(), 5);
+    }
+
+    #[test]
+    fn test_write_and_parse() {
+        let mut puzzle = Puzzle::default();
+        puzzle.set(5, Some(4));
+        puzzle.cages.push(Cage { sum: 3, squares: vec![0, 1] });
+        puzzle.variants.push(Variant::Windoku);
+
+        let mut writer = TraceWriter::new(Vec::new(), &puzzle).unwrap();
+        writer.write(0, Some(1), Some("try")).unwrap();
+        writer.write(0, None, Some("back track")).unwrap();
+        writer.write(1, Some(9), None).unwrap();
+        let text = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        let (parsed, edits) = parse(&text).unwrap();
+        assert_eq!(parsed, puzzle);
+        assert_eq!(
+            edits,
+            [
+                Edit { index: 0, previous: None, value: Some(1), reason: Some("try".into()) },
+                Edit { index: 0, previous: Some(1), value: None, reason: Some("back track".into()) },
+                Edit { index: 1, previous: None, value: Some(9), reason: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_edits() {
+        let puzzle = "0".repeat(81);
+        assert!(parse(&format!("# sudoku trace\npuzzle {puzzle}\n1 set 81 1")).is_err());
+        assert!(parse(&format!("# sudoku trace\npuzzle {puzzle}\n1 set 0 0")).is_err());
+        assert!(parse(&format!("# sudoku trace\npuzzle {puzzle}\n1 nudge 0")).is_err());
+        assert!(parse(&format!("puzzle {puzzle}")).is_err());
+    }
+
+    /// A trace with an edit somewhere in it, which often leaves it almost but
+    /// not quite valid.
+    fn damaged_trace() -> impl Strategy<Value = String> {
+        let mut puzzle = Puzzle::from_str("1...............\nregions aaab abbb cccd cddd\ncage 5 r1c2 r1c3").unwrap();
+        puzzle.variants.push(Variant::Diagonal);
+        let mut writer = TraceWriter::new(Vec::new(), &puzzle).unwrap();
+        writer.write(1, Some(2), Some("try")).unwrap();
+        writer.write(1, None, Some("backtrack")).unwrap();
+        writer.write(15, Some(4), None).unwrap();
+        let trace = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        (any::<prop::sample::Index>(), any::<char>(), 0..3).prop_map(move |(index, char, edit)| {
+            let mut chars: Vec<_> = trace.chars().collect();
+            let index = index.index(chars.len());
+            match edit {
+                0 => chars[index] = char,
+                1 => chars.insert(index, char),
+                _ => drop(chars.remove(index)),
+            }
+            chars.into_iter().collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_arbitrary(text in prop_oneof![damaged_trace(), "\\PC{0,200}"]) {
+            // Anything may be rejected, but nothing may panic, even when replayed.
+            if let Ok((mut puzzle, edits)) = parse(&text) {
+                for edit in &edits {
+                    edit.apply(&mut puzzle);
+                }
+            }
+        }
+    }
+}
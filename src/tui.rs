@@ -1,46 +1,135 @@
-use std::collections::HashSet;
-use std::sync::mpsc::SyncSender;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::DefaultTerminal;
 use ratatui::buffer::Buffer;
-use ratatui::layout::{Constraint, Offset, Position, Rect};
-use ratatui::style::Color;
+use ratatui::layout::{Constraint, Offset, Position, Rect, Size};
+use ratatui::style::{Color, Style};
+use ratatui::text::Text;
 use ratatui::widgets::Widget;
 
-use crate::puzzle::Puzzle;
-use crate::tui::layout::{Cell, LAYOUT, X_CELL_COUNT, Y_CELL_COUNT};
-use crate::util::{DigitChar, DivRem};
+use crate::cancel::CancellationToken;
+use crate::layout::{self, Cell, Layout};
+use crate::puzzle::{Dimensions, Puzzle};
+use crate::util::DigitChar;
 
-mod layout;
+pub mod animation;
+
+/// How many characters wide each square is drawn.
+const CELL_WIDTH: usize = 3;
+/// The style of the lines around Killer Sudoku cages, and their sums.
+const CAGE_STYLE: Style = Style::new().fg(Color::Yellow);
+/// Space between grids drawn side by side.
+const GRID_GAP: u16 = 4;
 
 pub trait KeyHandler: Clone + Default + Sized {
-    fn handle_key(self, _tui: &mut Tui<Self>, _puzzle: &mut Puzzle, _key: KeyEvent) -> Self {
+    /// What key presses are able to edit.
+    type State;
+
+    fn handle_key(self, _tui: &mut Tui<Self>, _state: &mut Self::State, _key: KeyEvent) -> Self {
         self
     }
 }
 
-impl KeyHandler for () {}
+impl KeyHandler for () {
+    type State = ();
+}
+
+/// Space between a grid and its panel.
+const PANEL_GAP: u16 = 2;
+
+/// A puzzle to draw, along with what to show around it.
+pub struct Grid<'a> {
+    pub puzzle: &'a Puzzle,
+    /// Shown above the grid.
+    pub title: Option<String>,
+    /// Styles to apply to individual squares, on top of any the TUI applies.
+    pub styles: HashMap<usize, Style>,
+    /// Lines of text shown to the right of the grid.
+    pub panel: Vec<String>,
+}
+
+impl<'a> Grid<'a> {
+    pub fn new(puzzle: &'a Puzzle) -> Self {
+        Self { puzzle, title: None, styles: HashMap::new(), panel: Vec::new() }
+    }
+}
+
+/// Where a set of grids are drawn, relative to each other.
+struct Arrangement {
+    layouts: Vec<Layout>,
+    grid_width: u16,
+    grid_height: u16,
+    title_height: u16,
+    /// The width of the widest panel, including the gap before it.
+    panel_width: u16,
+    /// The width of a grid along with its panel.
+    block_width: u16,
+}
+
+impl Arrangement {
+    fn new(grids: &[Grid], compact: bool) -> Self {
+        let layouts: Vec<_> = grids
+            .iter()
+            .map(|grid| if compact { Layout::compact(grid.puzzle) } else { Layout::new(grid.puzzle, CELL_WIDTH) })
+            .collect();
+        let grid_width = layouts.iter().map(Layout::width).max().unwrap_or_default();
+        let grid_height = layouts.iter().map(Layout::height).max().unwrap_or_default();
+        let title_height = grids.iter().any(|grid| grid.title.is_some()) as u16;
+        // The panel is only ever a handful of short lines, so this cast is safe.
+        let panel_width = grids.iter().flat_map(|grid| &grid.panel).map(|line| line.chars().count()).max();
+        let panel_width = panel_width.map(|width| width as u16 + PANEL_GAP).unwrap_or_default();
+        Self { layouts, grid_width, grid_height, title_height, panel_width, block_width: grid_width + panel_width }
+    }
+
+    /// Arrange the grids to fit within `size`, drawing them compactly if need
+    /// be.
+    fn fit(grids: &[Grid], size: Size) -> Option<Self> {
+        [false, true]
+            .into_iter()
+            .map(|compact| Self::new(grids, compact))
+            .find(|arrangement| arrangement.width() <= size.width && arrangement.height() <= size.height)
+    }
+
+    fn width(&self) -> u16 {
+        // This cast is safe since we only ever draw a couple of grids.
+        let count = self.layouts.len() as u16;
+        count * self.block_width + count.saturating_sub(1) * GRID_GAP
+    }
+
+    fn height(&self) -> u16 {
+        self.title_height + self.grid_height
+    }
+}
 
 pub struct Tui<K: KeyHandler = ()> {
     pub cursor_square_index: Option<usize>,
     pub invalid_squares: HashSet<usize>,
+    /// Whether to shade the row, column and box of the square under the cursor,
+    /// and to highlight the other squares which contain its digit.
+    pub highlight_peers: bool,
+    /// Text to show beneath the grid, which may span multiple lines.
+    pub status: Option<String>,
 
     terminal: DefaultTerminal,
-    kill_channel: SyncSender<()>,
+    /// Cancelled when the user quits, which also stops anything else holding
+    /// the token, such as solvers being animated.
+    cancel: CancellationToken,
     key_handler: K,
 }
 
 impl<K: KeyHandler> Tui<K> {
-    pub fn init(kill_channel: SyncSender<()>) -> Self {
+    pub fn init(cancel: CancellationToken) -> Self {
         Self {
             terminal: ratatui::init(),
-            kill_channel,
+            cancel,
             cursor_square_index: None,
             key_handler: K::default(),
             invalid_squares: HashSet::new(),
+            highlight_peers: false,
+            status: None,
         }
     }
 
@@ -49,71 +138,201 @@ impl<K: KeyHandler> Tui<K> {
         self
     }
 
-    pub fn render(&mut self, puzzle: &mut Puzzle) -> Result<()> {
-        const IMMEDIATE: Duration = Duration::from_secs(0);
+    pub fn with_peer_highlighting(mut self) -> Self {
+        self.highlight_peers = true;
+        self
+    }
 
-        while let Ok(true) = event::poll(IMMEDIATE) {
+    /// Whether the user has asked to quit, or the token was cancelled some
+    /// other way.
+    pub fn is_killed(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Cancel the token that the TUI was given, since the user has asked to
+    /// quit.
+    pub fn kill(&mut self) {
+        self.cancel.cancel();
+    }
+
+    pub fn key_handler_mut(&mut self) -> &mut K {
+        &mut self.key_handler
+    }
+
+    /// Wait up to `timeout` for input and handle any that arrives.
+    ///
+    /// This returns as soon as the first event has been handled, so callers
+    /// which need to wait for a fixed amount of time should call this in a loop
+    /// until their deadline has passed.
+    pub fn handle_events(&mut self, state: &mut K::State, timeout: Duration) -> Result<()> {
+        let mut timeout = timeout;
+        while let Ok(true) = event::poll(timeout) {
+            timeout = Duration::ZERO;
             match event::read()? {
                 Event::Key(event) => {
-                    // BUG: This doesn't work if we have large values for --animation-delay-ms...
                     if event.modifiers.contains(KeyModifiers::CONTROL) && event.code == KeyCode::Char('c') {
-                        self.kill_channel.send(())?;
+                        self.kill();
                         return Ok(());
                     }
 
-                    self.key_handler = self.key_handler.clone().handle_key(self, puzzle, event);
+                    self.key_handler = self.key_handler.clone().handle_key(self, state, event);
                 }
+                // Pick up the new size straight away, so that the next draw lays the grids out
+                // for it from scratch.
+                Event::Resize(..) => self.terminal.autoresize()?,
                 _ => {}
             }
         }
+        Ok(())
+    }
+
+    /// Draw the grids side by side, with the status beneath them.
+    ///
+    /// The grids are drawn compactly if they won't otherwise fit in the
+    /// terminal, and if they won't fit even then a message asking for a larger
+    /// terminal is drawn instead.
+    ///
+    /// The cursor and square highlighting only apply to the first grid.
+    pub fn draw(&mut self, grids: &[Grid]) -> Result<()> {
+        let Some(first) = grids.first() else {
+            return Ok(());
+        };
 
-        let cursor_position = self.cursor_position();
-        let invalid_squares = &self.invalid_squares;
+        let status = self.status.as_deref().map(|status| Text::from(status).centered());
+        // The status is only ever a handful of lines, so this cast is safe.
+        let status_height = status.as_ref().map(|status| status.height() as u16).unwrap_or_default();
+        let size = self.terminal.size()?;
+        let Some(arrangement) =
+            Arrangement::fit(grids, Size { height: size.height.saturating_sub(status_height), ..size })
+        else {
+            let needed = Arrangement::new(grids, true);
+            let message =
+                format!("terminal too small\nneed at least {}x{}", needed.width(), needed.height() + status_height);
+            self.terminal.draw(|frame| {
+                let area = frame.area();
+                let text = Text::from(message).centered();
+                let vertical = Constraint::Length(text.height().min(area.height as usize) as u16);
+                frame.render_widget(text, area.centered_vertically(vertical));
+            })?;
+            return Ok(());
+        };
+
+        let layouts = &arrangement.layouts;
+        let cursor_position = self.cursor_square_index.map(|index| Position::from(layouts[0].square_position(index)));
+        let tui_styles = self.square_styles(first.puzzle);
 
         self.terminal.draw(|frame| {
-            // These casts to u16s are safe since the dimensions of the grid layout array
-            // will never overflow u16.
-            let horizontal = Constraint::Length(LAYOUT[0].len() as u16);
-            let vertical = Constraint::Length(LAYOUT.len() as u16);
+            let area = frame.area();
+            let Arrangement { grid_width, grid_height, title_height, panel_width, block_width, .. } = arrangement;
+            let horizontal = Constraint::Length(arrangement.width());
+            let vertical = Constraint::Length(arrangement.height());
+            let grids_rect = area.centered(horizontal, vertical);
+
+            for (i, grid) in grids.iter().enumerate() {
+                // This cast is safe since we only ever draw a couple of grids.
+                let x = grids_rect.x + i as u16 * (block_width + GRID_GAP);
+                let grid_rect = Rect { x, y: grids_rect.y + title_height, width: grid_width, height: grid_height };
 
-            let grid_rect = frame.area().centered(horizontal, vertical);
+                if let Some(title) = &grid.title {
+                    let title_rect = Rect { y: grids_rect.y, height: 1, ..grid_rect };
+                    frame.render_widget(Text::from(title.as_str()).centered(), title_rect.intersection(area));
+                }
 
-            if let Some(cursor_position) = cursor_position {
-                // u16 -> i32 is always safe.
-                frame.set_cursor_position(cursor_position + Offset { x: grid_rect.x as i32, y: grid_rect.y as i32 });
+                let mut square_styles = if i == 0 { tui_styles.clone() } else { HashMap::new() };
+                for (&index, &style) in &grid.styles {
+                    let base = square_styles.entry(index).or_default();
+                    *base = base.patch(style);
+                }
+
+                if i == 0
+                    && let Some(cursor_position) = cursor_position
+                {
+                    // u16 -> i32 is always safe.
+                    frame
+                        .set_cursor_position(cursor_position + Offset { x: grid_rect.x as i32, y: grid_rect.y as i32 });
+                }
+
+                let widget = GridWidget { puzzle: grid.puzzle, layout: &layouts[i], square_styles: &square_styles };
+                frame.render_widget(widget, grid_rect.intersection(area));
+
+                if !grid.panel.is_empty() {
+                    let panel_rect =
+                        Rect { x: grid_rect.right() + PANEL_GAP, width: panel_width - PANEL_GAP, ..grid_rect };
+                    let panel = Text::from_iter(grid.panel.iter().map(String::as_str));
+                    frame.render_widget(panel, panel_rect.intersection(area));
+                }
             }
 
-            frame.render_widget(GridWidget { puzzle, invalid_squares }, grid_rect);
+            if let Some(status) = status {
+                let status_rect = Rect { y: grids_rect.bottom(), height: status_height, ..area };
+                frame.render_widget(status, status_rect.intersection(area));
+            }
         })?;
 
         Ok(())
     }
 
-    fn cursor_position(&self) -> Option<Position> {
-        let index = self.cursor_square_index?;
-        let (row, col) = index.div_rem(9);
-        // TODO: Derive these multipliers from the layout file?
-        Some(Position { x: (col * 4 + 2) as u16, y: (row * 2 + 1) as u16 })
+    /// Build the styles to apply on top of each square's default style.
+    fn square_styles(&self, puzzle: &Puzzle) -> HashMap<usize, Style> {
+        let mut styles = HashMap::new();
+
+        if self.highlight_peers
+            && let Some(cursor) = self.cursor_square_index
+        {
+            let digit = puzzle.get(cursor);
+            let peers = puzzle.peers(cursor);
+            for index in 0..puzzle.data.len() {
+                if index == cursor {
+                    continue;
+                }
+                if digit.is_some() && puzzle.get(index) == digit {
+                    styles.insert(index, Style::new().bg(Color::Blue));
+                } else if peers.contains(&index) {
+                    styles.insert(index, Style::new().bg(Color::DarkGray));
+                }
+            }
+        }
+
+        for &index in &self.invalid_squares {
+            if !puzzle.is_initially_filled(index) {
+                let style = styles.entry(index).or_default();
+                *style = style.fg(Color::Red);
+            }
+        }
+
+        styles
     }
 
-    pub fn move_cursor(&mut self, direction: Movement) -> Option<()> {
+    pub fn move_cursor(&mut self, dimensions: Dimensions, direction: Movement) -> Option<()> {
         let index = self.cursor_square_index?;
-        let (mut row, mut col) = index.div_rem(9);
+        let size = dimensions.size();
+        let (mut row, mut col) = dimensions.position(index);
         match direction {
-            Movement::Up => row = row.wrapping_sub(1).min(8),
-            Movement::Down => row = (row + 1) % 9,
-            Movement::Left => col = col.wrapping_sub(1).min(8),
-            Movement::Right => col = (col + 1) % 9,
+            Movement::Up => row = row.wrapping_sub(1).min(size - 1),
+            Movement::Down => row = (row + 1) % size,
+            Movement::Left => col = col.wrapping_sub(1).min(size - 1),
+            Movement::Right => col = (col + 1) % size,
             Movement::To { row: r, column: c } => {
                 row = r;
                 col = c;
             }
         }
-        self.cursor_square_index = Some(row * 9 + col);
+        self.cursor_square_index = Some(dimensions.index(row, col));
         Some(())
     }
 }
 
+impl<K: KeyHandler<State = Puzzle>> Tui<K> {
+    /// Handle any pending input, then draw `puzzle`.
+    pub fn render(&mut self, puzzle: &mut Puzzle) -> Result<()> {
+        self.handle_events(puzzle, Duration::ZERO)?;
+        if self.is_killed() {
+            return Ok(());
+        }
+        self.draw(&[Grid::new(puzzle)])
+    }
+}
+
 impl<K: KeyHandler> Drop for Tui<K> {
     fn drop(&mut self) {
         ratatui::restore();
@@ -130,7 +349,9 @@ pub enum Movement {
 
 struct GridWidget<'a> {
     puzzle: &'a Puzzle,
-    invalid_squares: &'a HashSet<usize>,
+    layout: &'a Layout,
+    /// Styles to apply to individual squares, keyed by square index.
+    square_styles: &'a HashMap<usize, Style>,
 }
 
 impl GridWidget<'_> {
@@ -138,16 +359,82 @@ impl GridWidget<'_> {
         let char = self.puzzle.get(index).and_then(DigitChar::digit_char).unwrap_or(' ');
         cell.set_char(char);
 
-        if let Some(initially_filled) = &self.puzzle.initially_filled {
-            cell.set_fg(if initially_filled[index] { Color::Gray } else { Color::LightBlue });
+        if self.puzzle.initially_filled.is_some() {
+            cell.set_fg(if self.puzzle.is_initially_filled(index) { Color::Gray } else { Color::LightBlue });
         }
 
-        if self.invalid_squares.contains(&index) {
-            if !self.puzzle.initially_filled.map(|initially_filled| initially_filled[index]).unwrap_or_default() {
-                cell.set_fg(Color::Red);
+        if let Some(&style) = self.square_styles.get(&index) {
+            cell.set_style(style);
+        }
+    }
+
+    /// Outline each Killer Sudoku cage by highlighting the lines around it,
+    /// with its sum over the top-left corner.
+    ///
+    /// Lines only exist between boxes in a compact grid, so cages are only
+    /// partly outlined there.
+    fn render_cages(&self, area: Rect, buf: &mut Buffer) {
+        let dimensions = self.puzzle.dimensions;
+        let width = self.layout.cell_width();
+        for cage in &self.puzzle.cages {
+            let outside = |row: Option<usize>, col: Option<usize>| match (row, col) {
+                (Some(row), Some(col)) if row < dimensions.size() && col < dimensions.size() => {
+                    !cage.squares.contains(&dimensions.index(row, col))
+                }
+                _ => true,
+            };
+
+            for &index in &cage.squares {
+                let (row, col) = dimensions.position(index);
+                // There is always a border to the top and left of the grid, so these never
+                // underflow.
+                let Position { x, y } = self.layout.square_start(index).into();
+                let mut outline = Vec::new();
+                if outside(row.checked_sub(1), Some(col)) {
+                    outline.extend((x - 1..=x + width).map(|x| Position { x, y: y - 1 }));
+                }
+                if outside(Some(row + 1), Some(col)) {
+                    outline.extend((x - 1..=x + width).map(|x| Position { x, y: y + 1 }));
+                }
+                if outside(Some(row), col.checked_sub(1)) {
+                    outline.extend((y - 1..=y + 1).map(|y| Position { x: x - 1, y }));
+                }
+                if outside(Some(row), Some(col + 1)) {
+                    outline.extend((y - 1..=y + 1).map(|y| Position { x: x + width, y }));
+                }
+
+                for position in outline {
+                    if let Some(cell) = self.line_cell(position, area, buf) {
+                        cell.set_style(CAGE_STYLE);
+                    }
+                }
+            }
+
+            let Position { x, y } = self.layout.square_start(cage.first()).into();
+            for (i, char) in (0..).zip(cage.sum.to_string().chars()) {
+                if let Some(cell) = self.line_cell(Position { x: x + i, y: y - 1 }, area, buf) {
+                    cell.set_char(char).set_style(CAGE_STYLE);
+                }
             }
         }
     }
+
+    /// The buffer cell at `position` within the grid, if a line is drawn there
+    /// and it isn't clipped.
+    fn line_cell<'b>(
+        &self,
+        position: Position,
+        area: Rect,
+        buf: &'b mut Buffer,
+    ) -> Option<&'b mut ratatui::buffer::Cell> {
+        if position.x >= area.width || position.y >= area.height {
+            return None;
+        }
+        match self.layout.cell(position.into())? {
+            Cell::Glyph(_) => buf.cell_mut((area.x + position.x, area.y + position.y)),
+            _ => None,
+        }
+    }
 }
 
 impl Widget for GridWidget<'_> {
@@ -155,13 +442,20 @@ impl Widget for GridWidget<'_> {
     where
         Self: Sized,
     {
-        for y in 0..Y_CELL_COUNT {
-            for x in 0..X_CELL_COUNT {
-                // These u16 casts are safe since the dimensions of the grid are defined by a
-                // constant and will never overflow u16.
-                // TODO: Is this unwrap safe to do?
-                let cell = buf.cell_mut((area.x + x as u16, area.y + y as u16)).unwrap();
-                match LAYOUT[y][x] {
+        for (y, row) in self.layout.rows().iter().enumerate() {
+            for (x, layout_cell) in row.iter().enumerate() {
+                // These u16 casts are safe since the layout of even the largest puzzle will
+                // never overflow u16.
+                let (x, y) = (x as u16, y as u16);
+                // Anything outside of the area is clipped, rather than drawn over whatever is next
+                // to it.
+                if x >= area.width || y >= area.height {
+                    continue;
+                }
+                let Some(cell) = buf.cell_mut((area.x + x, area.y + y)) else {
+                    continue;
+                };
+                match *layout_cell {
                     Cell::Glyph(glyph) => {
                         cell.set_char(glyph);
                     }
@@ -174,5 +468,42 @@ impl Widget for GridWidget<'_> {
                 };
             }
         }
+
+        self.render_cages(area, buf);
+    }
+}
+
+impl From<layout::Position> for Position {
+    fn from(position: layout::Position) -> Self {
+        Self { x: position.x, y: position.y }
+    }
+}
+
+impl From<Position> for layout::Position {
+    fn from(position: Position) -> Self {
+        Self { x: position.x, y: position.y }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_arrangement_fit() {
+        let puzzle = Puzzle::default();
+        let mut grid = Grid::new(&puzzle);
+        grid.panel = vec!["step 10/20".to_string()];
+        let grids = [grid];
+
+        let arrangement = Arrangement::fit(&grids, Size { width: 80, height: 24 }).unwrap();
+        assert_eq!((arrangement.width(), arrangement.height()), (37 + PANEL_GAP + 10, 19));
+
+        // Too narrow for the full grid and its panel.
+        let arrangement = Arrangement::fit(&grids, Size { width: 40, height: 24 }).unwrap();
+        assert_eq!((arrangement.width(), arrangement.height()), (13 + PANEL_GAP + 10, 13));
+
+        assert!(Arrangement::fit(&grids, Size { width: 20, height: 24 }).is_none());
+        assert!(Arrangement::fit(&grids, Size { width: 80, height: 12 }).is_none());
     }
 }
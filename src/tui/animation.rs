@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::style::{Color, Style};
+
+use crate::cancel::CancellationToken;
+use crate::puzzle::Puzzle;
+use crate::trace::{Playback, SharedTrace};
+use crate::tui::{Grid, KeyHandler, Tui};
+
+const DEFAULT_DELAY_MS: u64 = 50;
+const MIN_DELAY: Duration = Duration::from_millis(1);
+const MAX_DELAY: Duration = Duration::from_secs(10);
+/// How long to block waiting for input when there is nothing to animate.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Animates one or more traces side by side, in lockstep.
+///
+/// The traces may still be being written by solvers on other threads, in
+/// which case the animation catches up with them as they go. Every edit is
+/// kept, so the animation can be paused, stepped through in either direction,
+/// and replayed once the solvers are done:
+/// - `Space` pauses and resumes.
+/// - `n` / `→` and `p` / `←` step forwards and backwards.
+/// - `Home` / `End` jump to the start and end, and `g<step>⏎` to a given step.
+/// - `+` / `-` speed up and slow down.
+/// - `q` quits.
+pub struct Animation {
+    tui: Tui<PlaybackKeys>,
+    lanes: Vec<Lane>,
+    /// The number of edits shown, which is shared between all lanes.
+    position: usize,
+    last_advance: Instant,
+}
+
+/// A single trace being animated.
+pub struct Lane {
+    title: Option<String>,
+    trace: SharedTrace,
+    playback: Playback,
+}
+
+impl Lane {
+    /// Animate `trace`, whose edits start from `puzzle`.
+    pub fn new(title: Option<String>, puzzle: Puzzle, trace: SharedTrace) -> Self {
+        Self { title, trace, playback: Playback::new(puzzle) }
+    }
+
+    fn seek(&mut self, position: usize) {
+        self.playback.seek(&self.trace.lock().edits, position);
+        self.trace.read_to(position);
+    }
+}
+
+impl Drop for Lane {
+    /// Nothing is going to watch the rest of the trace, so let the solver
+    /// finish, if it's still going.
+    fn drop(&mut self) {
+        self.trace.release();
+    }
+}
+
+impl Animation {
+    pub fn init(lanes: Vec<Lane>, cancel: CancellationToken, delay_ms: Option<u64>) -> Self {
+        let mut tui = Tui::<PlaybackKeys>::init(cancel);
+        tui.key_handler_mut().delay = Duration::from_millis(delay_ms.unwrap_or(DEFAULT_DELAY_MS));
+        Self { tui, lanes, position: 0, last_advance: Instant::now() }
+    }
+
+    /// Run the animation until the user quits.
+    pub fn run(&mut self) -> Result<()> {
+        while !self.tui.is_killed() {
+            let (total, done) = self.progress();
+            let keys = self.tui.key_handler_mut();
+
+            let timeout = if let Some(seek) = keys.seek {
+                let target = match seek {
+                    Seek::To(target) => target,
+                    Seek::Forward => self.position + 1,
+                    Seek::Back => self.position.saturating_sub(1),
+                };
+                // If the solvers haven't got that far yet, go as far as we can and keep
+                // waiting for them.
+                let waiting = target > total && !done;
+                keys.seek = waiting.then_some(Seek::To(target));
+                self.seek(target.min(total));
+                if waiting { IDLE_POLL_INTERVAL } else { Duration::ZERO }
+            } else if keys.paused {
+                IDLE_POLL_INTERVAL
+            } else {
+                match keys.delay.checked_sub(self.last_advance.elapsed()) {
+                    Some(remaining) if !remaining.is_zero() => remaining,
+                    _ if self.position < total => {
+                        self.seek(self.position + 1);
+                        Duration::ZERO
+                    }
+                    // Waiting on the solvers, which shouldn't take as long as a full idle interval.
+                    _ if !done => keys.delay.min(IDLE_POLL_INTERVAL),
+                    _ => IDLE_POLL_INTERVAL,
+                }
+            };
+
+            self.tui.handle_events(&mut (), timeout)?;
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    /// The length of the longest trace, and whether all of the traces are
+    /// finished.
+    fn progress(&self) -> (usize, bool) {
+        self.lanes.iter().fold((0, true), |(total, done), lane| {
+            let trace = lane.trace.lock();
+            (total.max(trace.edits.len()), done && trace.done)
+        })
+    }
+
+    fn seek(&mut self, position: usize) {
+        self.position = position;
+        self.last_advance = Instant::now();
+        for lane in &mut self.lanes {
+            lane.seek(position);
+        }
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let (total, done) = self.progress();
+        let plus = if done { "" } else { "+" };
+
+        let traces: Vec<_> = self.lanes.iter().map(|lane| lane.trace.lock()).collect();
+        let grids: Vec<_> = self
+            .lanes
+            .iter()
+            .zip(&traces)
+            .map(|(lane, trace)| {
+                let playback = &lane.playback;
+                let plus = if trace.done { "" } else { "+" };
+                let mut panel = vec![
+                    format!("step {}/{}{plus}", playback.position(), trace.edits.len()),
+                    format!("depth {}", playback.depth()),
+                    format!("backtracks {}", playback.backtracks()),
+                ];
+
+                let mut styles = HashMap::new();
+                if let Some(edit) = playback.last_edit(&trace.edits) {
+                    let style = match edit.value {
+                        Some(_) => Style::new().fg(Color::Black).bg(Color::Green),
+                        None => Style::new().bg(Color::Red),
+                    };
+                    styles.insert(edit.index, style);
+                    if let Some(reason) = &edit.reason {
+                        panel.push(format!("last {reason}"));
+                    }
+                }
+
+                Grid { puzzle: playback.puzzle(), title: lane.title.clone(), styles, panel }
+            })
+            .collect();
+
+        let keys = self.tui.key_handler_mut();
+        let state = match &keys.goto {
+            Some(digits) => format!("goto: {digits}_"),
+            None if keys.paused => "paused".to_string(),
+            None => "playing".to_string(),
+        };
+        self.tui.status = Some(format!(
+            "step {}/{total}{plus} | {state} | delay {}ms\n\
+             [space] pause [←/→] step [g] goto [home/end] jump [+/-] speed [q] quit",
+            self.position,
+            keys.delay.as_millis()
+        ));
+
+        self.tui.draw(&grids)
+    }
+}
+
+/// Playback state of the animation, driven by the user's key presses.
+#[derive(Clone)]
+struct PlaybackKeys {
+    paused: bool,
+    delay: Duration,
+    /// Where in the traces the user has asked to move to.
+    seek: Option<Seek>,
+    /// Digits typed so far for a `g<step>` jump.
+    goto: Option<String>,
+}
+
+impl Default for PlaybackKeys {
+    fn default() -> Self {
+        Self { paused: false, delay: Duration::from_millis(DEFAULT_DELAY_MS), seek: None, goto: None }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Seek {
+    To(usize),
+    Forward,
+    Back,
+}
+
+impl KeyHandler for PlaybackKeys {
+    type State = ();
+
+    fn handle_key(mut self, tui: &mut Tui<Self>, _state: &mut (), key: KeyEvent) -> Self {
+        if let Some(digits) = &mut self.goto {
+            match key.code {
+                KeyCode::Char(char) if char.is_ascii_digit() => digits.push(char),
+                KeyCode::Backspace => {
+                    digits.pop();
+                }
+                KeyCode::Enter => {
+                    self.seek = digits.parse().ok().map(Seek::To);
+                    self.paused = true;
+                    self.goto = None;
+                }
+                KeyCode::Esc => self.goto = None,
+                _ => {}
+            }
+            return self;
+        }
+
+        match key.code {
+            KeyCode::Char(' ') => self.paused = !self.paused,
+            KeyCode::Char('n') | KeyCode::Right => {
+                self.paused = true;
+                self.seek = Some(Seek::Forward);
+            }
+            KeyCode::Char('p') | KeyCode::Left => {
+                self.paused = true;
+                self.seek = Some(Seek::Back);
+            }
+            KeyCode::Home => self.seek = Some(Seek::To(0)),
+            KeyCode::End => self.seek = Some(Seek::To(usize::MAX)),
+            KeyCode::Char('g') => self.goto = Some(String::new()),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.delay = (self.delay / 2).clamp(MIN_DELAY, MAX_DELAY),
+            KeyCode::Char('-') => self.delay = (self.delay * 2).clamp(MIN_DELAY, MAX_DELAY),
+            KeyCode::Char('q') => tui.kill(),
+            _ => {}
+        }
+        self
+    }
+}
@@ -18,12 +18,22 @@ where
 }
 
 pub trait DigitChar {
-    /// If this number is a single digit, return its ASCII char.
+    /// If this number can be written as a single character, return its ASCII
+    /// char.
+    ///
+    /// Numbers above 9 are written as letters, so 10 is `A`, 16 is `G` and 25
+    /// is `P`.
     fn digit_char(self) -> Option<char>;
 }
 
 impl DigitChar for u8 {
     fn digit_char(self) -> Option<char> {
-        (self < 10).then(|| (self + 48) as char)
+        char::from_digit(self as u32, 36).map(|char| char.to_ascii_uppercase())
     }
 }
+
+/// The inverse of [`DigitChar::digit_char`], ignoring case.
+pub fn parse_digit_char(char: char) -> Option<u8> {
+    // This cast to u8 is safe since the digit will only ever be 0-35.
+    char.to_digit(36).map(|digit| digit as u8)
+}